@@ -25,13 +25,13 @@ impl Sphere {
 
         let t1 = (self.radius * self.radius) - d_squared;
 
-        let t = if t0 > t1 + std::f64::EPSILON {
+        let t = if t0 > t1 + f64::EPSILON {
             t0 - t1
         } else {
             t0 + t1
         };
 
-        if t > std::f64::EPSILON {
+        if t > f64::EPSILON {
             return Some((*ro + *rd * t, t));
         }
         None
@@ -52,7 +52,7 @@ impl Sphere {
         let view_dir = (*origin - *target).normalize();
         let half_dir = (light_dir + view_dir).normalize();
 
-        let lambertian = normal.dot(light_dir).max(0.).min(1.) as f32;
+        let lambertian = normal.dot(light_dir).clamp(0., 1.) as f32;
         let specular = if lambertian > 0.0 {
             half_dir.dot(normal).max(0.).powi(128).min(1.) as f32
         } else {
@@ -80,6 +80,7 @@ fn main() {
     let camera = Frustum {
         origin: Point3::<WorldSpace>::new(0.0, 0.0, 10.0),
         target: Point3::<WorldSpace>::new(0.0, 0.0, 0.0),
+        up: Frustum::UP,
         fovy: 45.0,
         ncp: 1.0,
         fcp: 20.0,
@@ -92,7 +93,7 @@ fn main() {
         .collect::<Vec<_>>()
         .par_iter()
         .map(|(_x, _y, ro, rd)| {
-            let color: Srgb = match sphere.intersect(&ro, &rd) {
+            let color: Srgb = match sphere.intersect(ro, rd) {
                 Some((target, _)) => {
                     sphere.color(&target, &light, &camera.origin)
                 }
@@ -25,13 +25,13 @@ impl Sphere {
 
         let t1 = (self.radius * self.radius) - d_squared;
 
-        let t = if t0 > t1 + std::f64::EPSILON {
+        let t = if t0 > t1 + f64::EPSILON {
             t0 - t1
         } else {
             t0 + t1
         };
 
-        if t > std::f64::EPSILON {
+        if t > f64::EPSILON {
             return Some((*ro + *rd * t, t));
         }
         None
@@ -52,7 +52,7 @@ impl Sphere {
         let view_dir = (*origin - *target).normalize();
         let half_dir = (light_dir + view_dir).normalize();
 
-        let lambertian = normal.dot(light_dir).max(0.).min(1.) as f32;
+        let lambertian = normal.dot(light_dir).clamp(0., 1.) as f32;
         let specular = if lambertian > 0.0 {
             half_dir.dot(normal).max(0.).powi(128).min(1.) as f32
         } else {
@@ -82,6 +82,7 @@ fn main() {
             Frustum {
                 origin: Point3::<WorldSpace>::new(-10.0, 0.0, 0.0),
                 target: Point3::<WorldSpace>::new(0.0, 0.0, 0.0),
+                up: Frustum::UP,
                 fovy: 45.0,
                 ncp: 1.0,
                 fcp: 20.0,
@@ -91,6 +92,7 @@ fn main() {
             Frustum {
                 origin: Point3::<WorldSpace>::new(-7.0, 0.0, 7.0),
                 target: Point3::<WorldSpace>::new(0.0, 0.0, 0.0),
+                up: Frustum::UP,
                 fovy: 40.0,
                 ncp: 1.0,
                 fcp: 20.0,
@@ -100,6 +102,7 @@ fn main() {
             Frustum {
                 origin: Point3::<WorldSpace>::new(0.0, 0.0, 10.0),
                 target: Point3::<WorldSpace>::new(0.0, 0.0, 0.0),
+                up: Frustum::UP,
                 fovy: 30.0,
                 ncp: 1.0,
                 fcp: 20.0,
@@ -109,6 +112,7 @@ fn main() {
             Frustum {
                 origin: Point3::<WorldSpace>::new(7.0, 0.0, 7.0),
                 target: Point3::<WorldSpace>::new(0.0, 0.0, 0.0),
+                up: Frustum::UP,
                 fovy: 40.0,
                 ncp: 1.0,
                 fcp: 20.0,
@@ -118,6 +122,7 @@ fn main() {
             Frustum {
                 origin: Point3::<WorldSpace>::new(10.0, 0.0, 0.0),
                 target: Point3::<WorldSpace>::new(0.0, 0.0, 0.0),
+                up: Frustum::UP,
                 fovy: 45.0,
                 ncp: 1.0,
                 fcp: 20.0,
@@ -125,7 +130,8 @@ fn main() {
                 height: 200,
             },
         ],
-        frames_per_unit: 1,
+        frames_per_unit: 1.0,
+        ..Default::default()
     };
 
     camera_path
@@ -139,7 +145,7 @@ fn main() {
                 .collect::<Vec<_>>()
                 .par_iter()
                 .map(|(_x, _y, ro, rd)| {
-                    let color: Srgb = match sphere.intersect(&ro, &rd) {
+                    let color: Srgb = match sphere.intersect(ro, rd) {
                         Some((target, _)) => {
                             sphere.color(&target, &light, &camera.origin)
                         }
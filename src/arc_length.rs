@@ -0,0 +1,236 @@
+use crate::spline::hermite;
+
+/// Relative error bound of the segment lengths.
+pub const ARC_LENGTH_TOLERANCE: f64 = 1e-6;
+
+/// Maximal number of times an interval is halved by the quadrature.
+const MAX_DEPTH: usize = 16;
+
+/// Step of the central differences used to calculate the speed.
+const SPEED_STEP: f64 = 1e-5;
+
+/// Arc-length table of a curve on `[0, 1]`.
+///
+/// The length is integrated with adaptive Simpson quadrature up to a
+/// relative error of `ARC_LENGTH_TOLERANCE`. The nodes of the quadrature
+/// are kept to invert the arc length, so a curve can be sampled at
+/// equidistant points. Intervals are refined until the inverse meets the
+/// same error bound.
+#[derive(Debug, Clone)]
+pub struct ArcLength {
+    /// Parameter, length up to the parameter and speed at each node.
+    nodes: Vec<(f64, f64, f64)>,
+}
+
+impl ArcLength {
+    /// Integrates the length of `curve`, where `distance` measures the
+    /// distance between two points of the curve.
+    pub fn new<T, C, D>(curve: C, distance: D) -> Self
+    where
+        C: Fn(f64) -> T,
+        D: Fn(&T, &T) -> f64,
+    {
+        let speed = |s: f64| {
+            distance(&curve(s - SPEED_STEP), &curve(s + SPEED_STEP))
+                / (2. * SPEED_STEP)
+        };
+
+        // A coarse first pass on four intervals scales the tolerance and
+        // makes sure no feature between the initial samples is missed.
+        let speeds = (0..=8).map(|i| speed(i as f64 / 8.)).collect::<Vec<_>>();
+        let coarse = (0..4)
+            .map(|i| {
+                simpson(
+                    0.25,
+                    speeds[2 * i],
+                    speeds[2 * i + 1],
+                    speeds[2 * i + 2],
+                )
+            })
+            .collect::<Vec<_>>();
+        let tolerance = coarse.iter().sum::<f64>() * ARC_LENGTH_TOLERANCE / 4.;
+
+        let mut nodes = vec![(0., 0., speeds[0])];
+        for (i, whole) in coarse.iter().enumerate() {
+            let a = i as f64 * 0.25;
+            integrate(
+                &speed,
+                (a, a + 0.25),
+                (speeds[2 * i], speeds[2 * i + 1], speeds[2 * i + 2]),
+                *whole,
+                tolerance,
+                MAX_DEPTH,
+                &mut nodes,
+            );
+        }
+
+        ArcLength { nodes }
+    }
+
+    /// Table of a curve without extent.
+    pub fn zero() -> Self {
+        ArcLength {
+            nodes: vec![(0., 0., 0.), (1., 0., 0.)],
+        }
+    }
+
+    pub fn length(&self) -> f64 {
+        self.nodes.last().map_or(0., |node| node.1)
+    }
+
+    /// Calculates the length of the curve up to the parameter `s`.
+    pub fn length_at(&self, s: f64) -> f64 {
+        if s <= 0. {
+            return 0.;
+        }
+        if s >= 1. {
+            return self.length();
+        }
+
+        let idx = self
+            .nodes
+            .partition_point(|node| node.0 < s)
+            .clamp(1, self.nodes.len() - 1);
+        let (s0, l0, v0) = self.nodes[idx - 1];
+        let (s1, l1, v1) = self.nodes[idx];
+        let h = s1 - s0;
+
+        hermite(l0, v0 * h, l1, v1 * h, (s - s0) / h).clamp(l0, l1)
+    }
+
+    /// Calculates the curve parameter at which the curve has the given
+    /// length. The parameter is interpolated between the nodes with a cubic
+    /// Hermite spline, since the inverse of the speed is the derivative of
+    /// the parameter.
+    pub fn parameter_at(&self, distance: f64) -> f64 {
+        if distance <= 0. {
+            return 0.;
+        }
+        if distance >= self.length() {
+            return 1.;
+        }
+
+        let idx = self
+            .nodes
+            .partition_point(|node| node.1 < distance)
+            .clamp(1, self.nodes.len() - 1);
+        let (s0, l0, v0) = self.nodes[idx - 1];
+        let (s1, l1, v1) = self.nodes[idx];
+
+        let h = l1 - l0;
+        if h < f64::EPSILON {
+            return s0;
+        }
+        let t = (distance - l0) / h;
+
+        if v0 < f64::EPSILON || v1 < f64::EPSILON {
+            return s0 + (s1 - s0) * t;
+        }
+
+        hermite(s0, h / v0, s1, h / v1, t).clamp(s0, s1)
+    }
+}
+
+fn simpson(width: f64, fa: f64, fm: f64, fb: f64) -> f64 {
+    width / 6. * (fa + 4. * fm + fb)
+}
+
+/// Adaptive Simpson quadrature of `speed` on `interval`, appending a node
+/// for the midpoint and the end of every accepted interval.
+fn integrate<F: Fn(f64) -> f64>(
+    speed: &F,
+    interval: (f64, f64),
+    speeds: (f64, f64, f64),
+    whole: f64,
+    tolerance: f64,
+    depth: usize,
+    nodes: &mut Vec<(f64, f64, f64)>,
+) {
+    let (a, b) = interval;
+    let (fa, fm, fb) = speeds;
+    let m = (a + b) / 2.;
+    let (flm, frm) = (speed((a + m) / 2.), speed((m + b) / 2.));
+
+    let left = simpson(m - a, fa, flm, fm);
+    let right = simpson(b - m, fm, frm, fb);
+    let error = left + right - whole;
+
+    // The inverse is interpolated from the ends of the interval as well, so
+    // it has to hit the midpoint within the tolerance.
+    let inverse_error = if fa < f64::EPSILON || fb < f64::EPSILON {
+        0.
+    } else {
+        let h = left + right;
+        (hermite(a, h / fa, b, h / fb, left / h) - m) * fm
+    };
+
+    if depth == 0
+        || (error.abs() <= 15. * tolerance && inverse_error.abs() <= tolerance)
+    {
+        // Richardson extrapolation of the two estimates.
+        let start = nodes.last().map_or(0., |node| node.1);
+        nodes.push((m, start + left + error / 30., fm));
+        nodes.push((b, start + left + right + error / 15., fb));
+    } else {
+        integrate(
+            speed,
+            (a, m),
+            (fa, flm, fm),
+            left,
+            tolerance / 2.,
+            depth - 1,
+            nodes,
+        );
+        integrate(
+            speed,
+            (m, b),
+            (fm, frm, fb),
+            right,
+            tolerance / 2.,
+            depth - 1,
+            nodes,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::arc_length::ArcLength;
+    use std::cell::Cell;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn test_arc_length() {
+        let evaluations = Cell::new(0);
+        let circle = ArcLength::new(
+            |s: f64| {
+                evaluations.set(evaluations.get() + 1);
+                let angle = (s + s * s) * PI / 2.;
+                (angle.cos(), angle.sin())
+            },
+            |a: &(f64, f64), b: &(f64, f64)| {
+                ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt()
+            },
+        );
+
+        assert!((circle.length() - PI).abs() < 1e-6 * PI);
+        assert!(evaluations.get() < 1000, "{}", evaluations.get());
+
+        // The curve speeds up, the inverse undoes it.
+        for i in 0..=10 {
+            let s = i as f64 / 10.;
+            let parameter = circle.parameter_at((s + s * s) * PI / 2.);
+            assert!((parameter - s).abs() < 1e-6, "{} {}", s, parameter);
+        }
+
+        for i in 0..=10 {
+            let s = i as f64 / 10.;
+            let length = circle.length_at(s);
+            assert!((length - (s + s * s) * PI / 2.).abs() < 1e-6);
+        }
+
+        let point = ArcLength::zero();
+        assert_eq!(point.length(), 0.);
+        assert_eq!(point.parameter_at(0.5), 1.);
+    }
+}
@@ -0,0 +1,100 @@
+use crate::easing::Easing;
+use crate::frustum::Frustum;
+
+/// Iterator which blends two sequences of frames, such as the frames of
+/// two paths, a path and the frames of a controller, or a path and
+/// `std::iter::repeat` of a static frustum. Both sequences advance
+/// together and the blend ends with the shorter one, see `Frustum::blend`.
+pub struct BlendIterator<A, B, W> {
+    from: A,
+    to: B,
+    weight: W,
+    index: usize,
+}
+
+impl<A, B, W> BlendIterator<A, B, W>
+where
+    A: Iterator<Item = Frustum>,
+    B: Iterator<Item = Frustum>,
+    W: FnMut(usize) -> f64,
+{
+    /// Blends with a weight given per frame index, 0 for `from` and 1 for
+    /// `to`.
+    pub fn new<IA, IB>(from: IA, to: IB, weight: W) -> Self
+    where
+        IA: IntoIterator<IntoIter = A>,
+        IB: IntoIterator<IntoIter = B>,
+    {
+        BlendIterator {
+            from: from.into_iter(),
+            to: to.into_iter(),
+            weight,
+            index: 0,
+        }
+    }
+}
+
+impl<A, B> BlendIterator<A, B, Box<dyn FnMut(usize) -> f64>>
+where
+    A: Iterator<Item = Frustum>,
+    B: Iterator<Item = Frustum>,
+{
+    /// Cuts from `from` to `to` without a hard cut: the first `start`
+    /// frames show `from`, the next `frames` frames fade over with the
+    /// given easing, and the remaining frames show `to`.
+    pub fn crossfade<IA, IB>(
+        from: IA,
+        to: IB,
+        start: usize,
+        frames: usize,
+        easing: Easing,
+    ) -> Self
+    where
+        IA: IntoIterator<IntoIter = A>,
+        IB: IntoIterator<IntoIter = B>,
+    {
+        BlendIterator::new(
+            from,
+            to,
+            Box::new(move |index: usize| {
+                if index < start {
+                    0.0
+                } else if index >= start + frames {
+                    1.0
+                } else {
+                    // The fade reaches `to` on its last frame.
+                    easing.apply((index - start + 1) as f64 / frames as f64)
+                }
+            }),
+        )
+    }
+}
+
+impl<A, B, W> Iterator for BlendIterator<A, B, W>
+where
+    A: Iterator<Item = Frustum>,
+    B: Iterator<Item = Frustum>,
+    W: FnMut(usize) -> f64,
+{
+    type Item = Frustum;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let from = self.from.next()?;
+        let to = self.to.next()?;
+        let weight = (self.weight)(self.index);
+        self.index += 1;
+
+        Some(from.blend(&to, weight))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (from_min, from_max) = self.from.size_hint();
+        let (to_min, to_max) = self.to.size_hint();
+        let max = match (from_max, to_max) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+
+        (from_min.min(to_min), max)
+    }
+}
@@ -0,0 +1,164 @@
+use crate::frustum::Frustum;
+use crate::types::*;
+
+/// All matrices of a frustum and their inverses.
+///
+/// The matrices are computed once and only recomputed by `update` if a
+/// field they depend on has changed, so the accessors are cheap to call.
+#[derive(Debug, Copy, Clone)]
+pub struct CameraMatrices {
+    frustum: Frustum,
+    view: Transform3<WorldSpace, CameraSpace>,
+    projection: Transform3<CameraSpace, ViewSpace>,
+    screen: Transform3<ViewSpace, ScreenSpace>,
+    view_projection: Transform3<WorldSpace, ViewSpace>,
+    world_to_screen: Transform3<WorldSpace, ScreenSpace>,
+    inverse_view: Transform3<CameraSpace, WorldSpace>,
+    inverse_projection: Transform3<ViewSpace, CameraSpace>,
+    inverse_screen: Transform3<ScreenSpace, ViewSpace>,
+    inverse_view_projection: Transform3<ViewSpace, WorldSpace>,
+    screen_to_world: Transform3<ScreenSpace, WorldSpace>,
+}
+
+impl CameraMatrices {
+    pub fn new(frustum: &Frustum) -> Self {
+        let view = frustum.view();
+        let projection = frustum.projection();
+        let screen = frustum.screen();
+
+        let mut matrices = CameraMatrices {
+            frustum: *frustum,
+            view,
+            projection,
+            screen,
+            view_projection: Transform3::identity(),
+            world_to_screen: Transform3::identity(),
+            inverse_view: invert(&view, "view"),
+            inverse_projection: invert(&projection, "projection"),
+            inverse_screen: invert(&screen, "screen"),
+            inverse_view_projection: Transform3::identity(),
+            screen_to_world: Transform3::identity(),
+        };
+        matrices.compose();
+        matrices
+    }
+
+    /// The frustum the matrices were computed for.
+    pub fn frustum(&self) -> &Frustum {
+        &self.frustum
+    }
+
+    /// Brings the matrices up to date with the given frustum. Only the
+    /// matrices which depend on a changed field are recomputed.
+    /// Returns whether anything was recomputed.
+    pub fn update(&mut self, frustum: &Frustum) -> bool {
+        if *frustum == self.frustum {
+            return false;
+        }
+
+        let old = self.frustum;
+        self.frustum = *frustum;
+
+        if old.origin != frustum.origin
+            || old.target != frustum.target
+            || old.up != frustum.up
+        {
+            self.view = frustum.view();
+            self.inverse_view = invert(&self.view, "view");
+        }
+
+        if old.fovy != frustum.fovy
+            || old.ncp != frustum.ncp
+            || old.fcp != frustum.fcp
+            || old.width != frustum.width
+            || old.height != frustum.height
+        {
+            self.projection = frustum.projection();
+            self.inverse_projection = invert(&self.projection, "projection");
+        }
+
+        if old.width != frustum.width || old.height != frustum.height {
+            self.screen = frustum.screen();
+            self.inverse_screen = invert(&self.screen, "screen");
+        }
+
+        self.compose();
+        true
+    }
+
+    pub fn view(&self) -> Transform3<WorldSpace, CameraSpace> {
+        self.view
+    }
+
+    pub fn projection(&self) -> Transform3<CameraSpace, ViewSpace> {
+        self.projection
+    }
+
+    pub fn screen(&self) -> Transform3<ViewSpace, ScreenSpace> {
+        self.screen
+    }
+
+    pub fn view_projection(&self) -> Transform3<WorldSpace, ViewSpace> {
+        self.view_projection
+    }
+
+    pub fn world_to_screen(&self) -> Transform3<WorldSpace, ScreenSpace> {
+        self.world_to_screen
+    }
+
+    pub fn inverse_view(&self) -> Transform3<CameraSpace, WorldSpace> {
+        self.inverse_view
+    }
+
+    pub fn inverse_projection(&self) -> Transform3<ViewSpace, CameraSpace> {
+        self.inverse_projection
+    }
+
+    pub fn inverse_screen(&self) -> Transform3<ScreenSpace, ViewSpace> {
+        self.inverse_screen
+    }
+
+    pub fn inverse_view_projection(&self) -> Transform3<ViewSpace, WorldSpace> {
+        self.inverse_view_projection
+    }
+
+    pub fn screen_to_world(&self) -> Transform3<ScreenSpace, WorldSpace> {
+        self.screen_to_world
+    }
+
+    /// Calculate for a given screen space coordinate the corresponding
+    /// ray origin and direction on the near clipping plane.
+    pub fn ray_from_ncp(
+        &self,
+        screen_coords: &Point2<ScreenSpace>,
+    ) -> Option<(Point3<WorldSpace>, Vec3<WorldSpace>)> {
+        let ro = self
+            .screen_to_world
+            .transform_point3d(screen_coords.to_3d())?;
+
+        let rd = (ro - self.frustum.origin).normalize();
+
+        Some((ro, rd))
+    }
+
+    fn compose(&mut self) {
+        self.view_projection = self.view.post_transform(&self.projection);
+        self.world_to_screen =
+            self.view_projection.post_transform(&self.screen);
+        self.inverse_view_projection =
+            self.inverse_projection.post_transform(&self.inverse_view);
+        self.screen_to_world = self
+            .inverse_screen
+            .post_transform(&self.inverse_projection)
+            .post_transform(&self.inverse_view);
+    }
+}
+
+pub(crate) fn invert<S, T>(
+    transform: &Transform3<S, T>,
+    name: &str,
+) -> Transform3<T, S> {
+    transform
+        .inverse()
+        .unwrap_or_else(|| panic!("Inversing {} failed.", name))
+}
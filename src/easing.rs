@@ -0,0 +1,152 @@
+/// Maps the progress along a path or a segment, both in `[0, 1]`, to
+/// change the speed of the camera without changing its path.
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub enum Easing {
+    /// Constant speed.
+    #[default]
+    Linear,
+    /// Starts at rest and accelerates.
+    EaseIn,
+    /// Decelerates and ends at rest.
+    EaseOut,
+    /// Starts and ends at rest.
+    EaseInOut,
+    /// Timing curve as in CSS, a cubic Bezier curve from `(0, 0)` to
+    /// `(1, 1)` with the control points `(x1, y1)` and `(x2, y2)`. The
+    /// x coordinates have to be in `[0, 1]`.
+    CubicBezier { x1: f64, y1: f64, x2: f64, y2: f64 },
+}
+
+impl Easing {
+    /// Calculates the eased progress.
+    pub fn apply(&self, t: f64) -> f64 {
+        let t = t.clamp(0., 1.);
+
+        match *self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t,
+            Easing::EaseOut => 1. - (1. - t) * (1. - t),
+            Easing::EaseInOut => t * t * (3. - 2. * t),
+            Easing::CubicBezier { x1, y1, x2, y2 } => {
+                let s = solve_bezier(x1, x2, t);
+                bezier(y1, y2, s)
+            }
+        }
+    }
+}
+
+/// Cubic Bezier curve from 0 to 1 with the control points `c1` and `c2`.
+fn bezier(c1: f64, c2: f64, s: f64) -> f64 {
+    let t = 1. - s;
+    3. * t * t * s * c1 + 3. * t * s * s * c2 + s * s * s
+}
+
+/// Finds the curve parameter where the x coordinate of the timing curve is
+/// `x`, with Newton's method and bisection as fallback.
+fn solve_bezier(x1: f64, x2: f64, x: f64) -> f64 {
+    let mut s = x;
+    for _ in 0..8 {
+        let error = bezier(x1, x2, s) - x;
+        if error.abs() < 1e-12 {
+            return s;
+        }
+        let t = 1. - s;
+        let slope =
+            3. * t * t * x1 + 6. * t * s * (x2 - x1) + 3. * s * s * (1. - x2);
+        if slope.abs() < 1e-9 {
+            break;
+        }
+        s -= error / slope;
+    }
+
+    let (mut low, mut high) = (0., 1.);
+    s = x;
+    for _ in 0..64 {
+        let error = bezier(x1, x2, s) - x;
+        if error.abs() < 1e-12 {
+            break;
+        }
+        if error < 0. {
+            low = s;
+        } else {
+            high = s;
+        }
+        s = (low + high) / 2.;
+    }
+    s
+}
+
+/// Trapezoidal velocity profile. The camera accelerates from rest to
+/// `max_speed`, cruises, and decelerates to rest at the end of the path.
+/// Short paths never reach `max_speed`.
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct VelocityProfile {
+    /// Speed in path units per second.
+    pub max_speed: f64,
+    /// Acceleration in path units per second squared.
+    pub max_acceleration: f64,
+    pub frames_per_second: f64,
+}
+
+impl VelocityProfile {
+    /// Time in seconds to travel `length`.
+    pub fn duration(&self, length: f64) -> f64 {
+        let (ramp_time, speed) = self.ramp(length);
+        if speed <= 0. {
+            return 0.;
+        }
+        let ramp_length = speed * ramp_time;
+
+        2. * ramp_time + (length - ramp_length) / speed
+    }
+
+    /// Distance travelled after `time` seconds on a path of `length`.
+    pub fn distance_at(&self, length: f64, time: f64) -> f64 {
+        let duration = self.duration(length);
+        let time = time.clamp(0., duration);
+        let (ramp_time, speed) = self.ramp(length);
+        let acceleration = self.max_acceleration;
+
+        if time < ramp_time {
+            0.5 * acceleration * time * time
+        } else if time < duration - ramp_time {
+            0.5 * speed * ramp_time + speed * (time - ramp_time)
+        } else {
+            let remaining = duration - time;
+            length - 0.5 * acceleration * remaining * remaining
+        }
+    }
+
+    /// Number of frames, each lasting `1 / frames_per_second`.
+    pub fn frame_count(&self, length: f64) -> usize {
+        (self.duration(length) * self.frames_per_second).round() as usize
+    }
+
+    /// Duration of the acceleration and the top speed reached.
+    fn ramp(&self, length: f64) -> (f64, f64) {
+        assert!(
+            self.max_acceleration > 0.,
+            "The maximal acceleration must be positive."
+        );
+
+        let ramp_time = self.max_speed / self.max_acceleration;
+        let ramp_length = 0.5 * self.max_speed * ramp_time;
+
+        if 2. * ramp_length <= length {
+            (ramp_time, self.max_speed)
+        } else {
+            // Triangular profile, the camera brakes right after
+            // accelerating.
+            let ramp_time = (length / self.max_acceleration).sqrt();
+            (ramp_time, self.max_acceleration * ramp_time)
+        }
+    }
+}
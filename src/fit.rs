@@ -0,0 +1,335 @@
+use crate::frustum::Frustum;
+use crate::frustum_path::{
+    FrustumPath, KeyTangents, PathMetric, PathParameter,
+};
+use crate::spline::{
+    catmull_rom_basis, hermite_basis, hermite_derivative, Spline, SplineKind,
+    SplineQ, SplineValue,
+};
+use crate::types::*;
+
+/// Weight which pulls each unknown towards its initial guess. It keeps the
+/// least-squares problem regular where few samples constrain a tangent.
+const PRIOR_WEIGHT: f64 = 1e-6;
+
+/// Largest distance between two unknowns that share a sample, the band
+/// width of the normal equations.
+const BANDWIDTH: usize = 3;
+
+/// Rounds of fitting and moving the samples to their closest points on
+/// the fitted curve.
+const REPARAMETERIZATIONS: usize = 4;
+
+/// Unknowns a sample depends on, with their weights.
+type Row = [(usize, f64); 4];
+
+impl FrustumPath {
+    /// Fits a smooth path with few key frustums to a sequence of samples,
+    /// such as recorded or tracked camera poses.
+    ///
+    /// Key frustums are added at the samples with the largest error until
+    /// origin and target of every sample are within `tolerance` of the
+    /// path. Origin and target follow a Hermite spline, whose key values
+    /// and tangents are fitted by least squares, so jitter below the
+    /// tolerance is smoothed out. Each sample is matched with its closest
+    /// point on the curve rather than a fixed position. The up direction,
+    /// the field of view and the clipping planes are fitted on the
+    /// Catmull-Rom spline through the keys.
+    ///
+    /// The path has one frame per sample, but the frames are spaced evenly
+    /// by path length, so the timing of the samples is not kept. A camera
+    /// which slowed down while recording moves at constant speed along the
+    /// fitted path.
+    pub fn fit(samples: &[Frustum], tolerance: f64) -> FrustumPath {
+        if samples.len() < 3 {
+            return FrustumPath {
+                key_frustums: samples.to_vec(),
+                total_frames: Some(samples.len()),
+                ..Default::default()
+            };
+        }
+
+        let mut keys = vec![0, samples.len() - 1];
+        loop {
+            // Each sample starts on the segment between the keys around it,
+            // spaced evenly in between.
+            let mut positions = (0..samples.len())
+                .map(|i| {
+                    let segment_idx = keys[1..]
+                        .iter()
+                        .position(|key| i < *key)
+                        .unwrap_or(keys.len() - 2);
+                    let (start, end) =
+                        (keys[segment_idx], keys[segment_idx + 1]);
+
+                    (segment_idx, (i - start) as f64 / (end - start) as f64)
+                })
+                .collect::<Vec<_>>();
+
+            let mut path = fit_keys(samples, &keys, &positions);
+            for _ in 0..REPARAMETERIZATIONS {
+                reparameterize(&path, samples, &keys, &mut positions);
+                path = fit_keys(samples, &keys, &positions);
+            }
+
+            let mut inserted = Vec::new();
+            for window in keys.windows(2) {
+                let worst = (window[0] + 1..window[1])
+                    .map(|i| {
+                        let (segment_idx, s) = positions[i];
+                        let parameter =
+                            PathParameter::Segment(segment_idx as f64 + s);
+                        let fitted = path
+                            .frustum_at(parameter)
+                            .expect("A fitted path has key frustums.");
+
+                        (
+                            i,
+                            PathMetric::TRANSLATION
+                                .distance(&samples[i], &fitted),
+                        )
+                    })
+                    .max_by(|a, b| a.1.total_cmp(&b.1));
+
+                if let Some((i, error)) = worst {
+                    if error > tolerance {
+                        inserted.push(i);
+                    }
+                }
+            }
+
+            if inserted.is_empty() {
+                return path;
+            }
+            keys.extend(inserted);
+            keys.sort_unstable();
+        }
+    }
+}
+
+/// Fits a path with key frustums at the given sample indices, where each
+/// sample is approximated at its segment and position on it.
+fn fit_keys(
+    samples: &[Frustum],
+    keys: &[usize],
+    positions: &[(usize, f64)],
+) -> FrustumPath {
+    let key_count = keys.len();
+
+    // Hermite unknowns alternate between key values and tangents.
+    let hermite_rows = positions
+        .iter()
+        .map(|&(segment_idx, s)| {
+            let w = hermite_basis(s, 0);
+            let idx = 2 * segment_idx;
+            [
+                (idx, w[0]),
+                (idx + 1, w[1]),
+                (idx + 2, w[2]),
+                (idx + 3, w[3]),
+            ]
+        })
+        .collect::<Vec<_>>();
+    let spline_rows = positions
+        .iter()
+        .map(|&(segment_idx, s)| {
+            let (idx0, idx1, idx2, idx3) =
+                SplineQ::get_control_points_for_segment(segment_idx, key_count);
+            let w = catmull_rom_basis(s, 0);
+            [
+                (idx0, w[0] / 2.),
+                (idx1, w[1] / 2.),
+                (idx2, w[2] / 2.),
+                (idx3, w[3] / 2.),
+            ]
+        })
+        .collect::<Vec<_>>();
+
+    let fit_hermite = |value: fn(&Frustum) -> Vec3<WorldSpace>| {
+        // Start from the samples at the keys and central differences.
+        let prior = (0..key_count)
+            .flat_map(|idx| {
+                let (previous, next) =
+                    (idx.saturating_sub(1), (idx + 1).min(key_count - 1));
+                let tangent = (value(&samples[keys[next]])
+                    - value(&samples[keys[previous]]))
+                    / (next - previous) as f64;
+
+                [value(&samples[keys[idx]]), tangent]
+            })
+            .collect::<Vec<_>>();
+        let values = samples.iter().map(value).collect::<Vec<_>>();
+
+        least_squares(&hermite_rows, &values, &prior)
+    };
+    let fit_spline = |value: fn(&Frustum) -> f64| {
+        let prior = keys
+            .iter()
+            .map(|key| value(&samples[*key]))
+            .collect::<Vec<_>>();
+        let values = samples.iter().map(value).collect::<Vec<_>>();
+
+        least_squares(&spline_rows, &values, &prior)
+    };
+
+    let origins = fit_hermite(|frustum| frustum.origin.to_vector());
+    let targets = fit_hermite(|frustum| frustum.target.to_vector());
+    let ups = [
+        fit_spline(|frustum| frustum.up.x),
+        fit_spline(|frustum| frustum.up.y),
+        fit_spline(|frustum| frustum.up.z),
+    ];
+    let fovys = fit_spline(|frustum| frustum.fovy);
+    let ncps = fit_spline(|frustum| frustum.ncp);
+    let fcps = fit_spline(|frustum| frustum.fcp);
+
+    let key_frustums = keys
+        .iter()
+        .enumerate()
+        .map(|(idx, key)| {
+            let sample = &samples[*key];
+            let up = Vec3::new(ups[0][idx], ups[1][idx], ups[2][idx]);
+
+            Frustum {
+                origin: origins[2 * idx].to_point(),
+                target: targets[2 * idx].to_point(),
+                up: if up.length() < f64::EPSILON {
+                    sample.up
+                } else {
+                    up.normalize()
+                },
+                fovy: fovys[idx],
+                ncp: ncps[idx],
+                fcp: fcps[idx],
+                width: sample.width,
+                height: sample.height,
+            }
+        })
+        .collect();
+    let key_tangents = (0..key_count)
+        .map(|idx| KeyTangents {
+            origin: origins[2 * idx + 1],
+            target: targets[2 * idx + 1],
+        })
+        .collect();
+
+    FrustumPath {
+        key_frustums,
+        total_frames: Some(samples.len()),
+        spline: SplineKind::Hermite,
+        key_tangents,
+        ..Default::default()
+    }
+}
+
+/// Moves each sample to the position on its segment closest to the fitted
+/// origin and target, with a step of Newton's method. Samples at keys stay
+/// at their keys.
+fn reparameterize(
+    path: &FrustumPath,
+    samples: &[Frustum],
+    keys: &[usize],
+    positions: &mut [(usize, f64)],
+) {
+    for (i, (segment_idx, s)) in positions.iter_mut().enumerate() {
+        if keys.binary_search(&i).is_ok() {
+            continue;
+        }
+
+        let (k1, k2) = (
+            &path.key_frustums[*segment_idx],
+            &path.key_frustums[*segment_idx + 1],
+        );
+        let (t1, t2) = (
+            &path.key_tangents[*segment_idx],
+            &path.key_tangents[*segment_idx + 1],
+        );
+        let derivatives = |order| {
+            (
+                hermite_derivative(
+                    k1.origin.to_vector(),
+                    t1.origin,
+                    k2.origin.to_vector(),
+                    t2.origin,
+                    *s,
+                    order,
+                ),
+                hermite_derivative(
+                    k1.target.to_vector(),
+                    t1.target,
+                    k2.target.to_vector(),
+                    t2.target,
+                    *s,
+                    order,
+                ),
+            )
+        };
+
+        let (origin, target) = derivatives(0);
+        let (origin_d1, target_d1) = derivatives(1);
+        let (origin_d2, target_d2) = derivatives(2);
+        let origin_error = origin - samples[i].origin.to_vector();
+        let target_error = target - samples[i].target.to_vector();
+
+        // Newton step on the squared distance.
+        let slope = origin_error.dot(origin_d1) + target_error.dot(target_d1);
+        let curvature = origin_d1.dot(origin_d1)
+            + target_d1.dot(target_d1)
+            + origin_error.dot(origin_d2)
+            + target_error.dot(target_d2);
+        if curvature > f64::EPSILON {
+            *s = (*s - slope / curvature).clamp(0., 1.);
+        }
+    }
+}
+
+/// Solves the linear least-squares problem where each row weighs the
+/// unknowns to approximate one value, regularized towards `prior`. The
+/// normal equations are banded, so they are solved by Gaussian elimination
+/// within the band.
+fn least_squares<V: SplineValue>(
+    rows: &[Row],
+    values: &[V],
+    prior: &[V],
+) -> Vec<V> {
+    let n = prior.len();
+    let mut matrix = vec![[0.; 2 * BANDWIDTH + 1]; n];
+    let mut rhs = prior.iter().map(|v| *v * PRIOR_WEIGHT).collect::<Vec<_>>();
+    for row in matrix.iter_mut() {
+        row[BANDWIDTH] = PRIOR_WEIGHT;
+    }
+
+    // Entry (i, j) is stored at `matrix[i][j + BANDWIDTH - i]`.
+    for (row, value) in rows.iter().zip(values) {
+        for &(i, wi) in row {
+            rhs[i] = rhs[i] + *value * wi;
+            for &(j, wj) in row {
+                matrix[i][j + BANDWIDTH - i] += wi * wj;
+            }
+        }
+    }
+
+    for k in 0..n {
+        let last = (k + BANDWIDTH).min(n - 1);
+        let pivot = matrix[k];
+        for i in k + 1..=last {
+            let factor = matrix[i][k + BANDWIDTH - i] / pivot[BANDWIDTH];
+            for j in k..=last {
+                matrix[i][j + BANDWIDTH - i] -=
+                    factor * pivot[j + BANDWIDTH - k];
+            }
+            rhs[i] = rhs[i] - rhs[k] * factor;
+        }
+    }
+
+    for k in (0..n).rev() {
+        let last = (k + BANDWIDTH).min(n - 1);
+        let mut value = rhs[k];
+        for j in k + 1..=last {
+            value = value - rhs[j] * matrix[k][j + BANDWIDTH - k];
+        }
+        rhs[k] = value * (1. / matrix[k][BANDWIDTH]);
+    }
+
+    rhs
+}
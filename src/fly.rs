@@ -0,0 +1,94 @@
+use crate::frustum::Frustum;
+use crate::types::*;
+use euclid::Angle;
+
+/// First-person controller which flies a frustum through the scene.
+///
+/// Movement follows the basis computed by `Frustum::basis`, which is the
+/// same basis the view matrix is built from. The target is kept at a
+/// constant distance in front of the origin.
+#[derive(Debug, Copy, Clone)]
+pub struct FlyController {
+    /// Speed in world units per second at full input.
+    pub speed: f64,
+    /// Rate per second with which the velocity follows the input.
+    /// Without damping the velocity follows the input immediately.
+    pub damping: Option<f64>,
+    /// Lower pitch limit in degrees.
+    pub min_pitch: f64,
+    /// Upper pitch limit in degrees.
+    pub max_pitch: f64,
+    frustum: Frustum,
+    velocity: Vec3<WorldSpace>,
+}
+
+impl FlyController {
+    pub fn new(frustum: Frustum) -> Self {
+        FlyController {
+            speed: 1.0,
+            damping: None,
+            min_pitch: -89.0,
+            max_pitch: 89.0,
+            frustum,
+            velocity: Vec3::zero(),
+        }
+    }
+
+    pub fn frustum(&self) -> &Frustum {
+        &self.frustum
+    }
+
+    /// Replaces the controlled frustum and stops all motion.
+    pub fn set_frustum(&mut self, frustum: Frustum) {
+        self.frustum = frustum;
+        self.velocity = Vec3::zero();
+    }
+
+    /// Current velocity in world units per second.
+    pub fn velocity(&self) -> Vec3<WorldSpace> {
+        self.velocity
+    }
+
+    /// Turns the view direction by the given angles in degrees. Yaw turns
+    /// around the up direction of the frustum, pitch around the right
+    /// direction of `Frustum::basis` and is clamped against the plane
+    /// orthogonal to the up direction.
+    pub fn look(&mut self, delta_yaw: f64, delta_pitch: f64) {
+        let (s, _, f) = self.frustum.basis();
+        let up = self.frustum.up.normalize();
+        let distance = (self.frustum.target - self.frustum.origin).length();
+
+        let pitch = f.dot(up).clamp(-1.0, 1.0).asin().to_degrees();
+        let new_pitch = (pitch + delta_pitch)
+            .max(self.min_pitch)
+            .min(self.max_pitch);
+
+        let yaw: Rotation3<WorldSpace, WorldSpace> =
+            Rotation3::around_axis(up, Angle::degrees(delta_yaw));
+        let s = yaw.transform_vector3d(s);
+        let pitch: Rotation3<WorldSpace, WorldSpace> =
+            Rotation3::around_axis(s, Angle::degrees(pitch - new_pitch));
+        let direction = pitch.transform_vector3d(yaw.transform_vector3d(f));
+
+        self.frustum.target = self.frustum.origin + direction * distance;
+    }
+
+    /// Advances the controller by `dt` seconds. The inputs are usually in
+    /// `[-1, 1]` and are scaled by `speed`.
+    pub fn update(&mut self, forward: f64, right: f64, up: f64, dt: f64) {
+        let (s, u, f) = self.frustum.basis();
+        let desired = (f * forward + s * right + u * up) * self.speed;
+
+        self.velocity = match self.damping {
+            Some(damping) => {
+                self.velocity
+                    + (desired - self.velocity) * (1.0 - (-damping * dt).exp())
+            }
+            None => desired,
+        };
+
+        let translation = self.velocity * dt;
+        self.frustum.origin += translation;
+        self.frustum.target += translation;
+    }
+}
@@ -0,0 +1,182 @@
+use crate::frustum::Frustum;
+use crate::types::*;
+
+/// Chase camera which follows a subject whose position is only known step
+/// by step, such as an object in a simulation.
+///
+/// Origin and target move towards their goals on critically damped
+/// springs, so the camera lags behind smoothly and settles without
+/// overshooting. The springs are solved exactly, so the motion does not
+/// depend on the step size.
+#[derive(Debug, Copy, Clone)]
+pub struct FollowController {
+    /// Position of the camera relative to the subject. In chase mode the
+    /// offset is given along the right, up and forward direction of the
+    /// motion of the subject, e.g. negative z to follow behind it.
+    pub offset: Vec3<WorldSpace>,
+    /// Keeps the camera behind the subject as it turns, see `offset`.
+    pub chase: bool,
+    /// Seconds the camera looks ahead of the subject along its velocity.
+    pub look_ahead: f64,
+    /// Radius around the followed point in which the subject moves
+    /// without the camera reacting.
+    pub dead_zone: f64,
+    /// Time in seconds in which the origin roughly catches up with its
+    /// goal. Without smoothing the origin follows immediately.
+    pub origin_smoothing: f64,
+    /// Time in seconds in which the target roughly catches up with its
+    /// goal.
+    pub target_smoothing: f64,
+    frustum: Frustum,
+    focus: Point3<WorldSpace>,
+    subject: Option<Point3<WorldSpace>>,
+    subject_velocity: Vec3<WorldSpace>,
+    heading: Vec3<WorldSpace>,
+    origin_velocity: Vec3<WorldSpace>,
+    target_velocity: Vec3<WorldSpace>,
+}
+
+impl FollowController {
+    /// Starts following a subject at the target of `frustum`, keeping the
+    /// current offset of the camera.
+    pub fn new(frustum: Frustum) -> Self {
+        let (_, _, f) = frustum.basis();
+
+        FollowController {
+            offset: frustum.origin - frustum.target,
+            chase: false,
+            look_ahead: 0.0,
+            dead_zone: 0.0,
+            origin_smoothing: 0.3,
+            target_smoothing: 0.3,
+            frustum,
+            focus: frustum.target,
+            subject: None,
+            subject_velocity: Vec3::zero(),
+            heading: horizontal(f, frustum.up).unwrap_or(f),
+            origin_velocity: Vec3::zero(),
+            target_velocity: Vec3::zero(),
+        }
+    }
+
+    pub fn frustum(&self) -> &Frustum {
+        &self.frustum
+    }
+
+    /// Replaces the controlled frustum and stops all motion. The followed
+    /// point is reset to the target.
+    pub fn set_frustum(&mut self, frustum: Frustum) {
+        let (_, _, f) = frustum.basis();
+
+        self.frustum = frustum;
+        self.focus = frustum.target;
+        self.subject = None;
+        self.subject_velocity = Vec3::zero();
+        self.heading = horizontal(f, frustum.up).unwrap_or(f);
+        self.origin_velocity = Vec3::zero();
+        self.target_velocity = Vec3::zero();
+    }
+
+    /// Velocity of the subject in world units per second, estimated from
+    /// its last two positions.
+    pub fn subject_velocity(&self) -> Vec3<WorldSpace> {
+        self.subject_velocity
+    }
+
+    /// Advances the controller by `dt` seconds, with the subject now at
+    /// `subject`.
+    pub fn update(&mut self, subject: &Point3<WorldSpace>, dt: f64) {
+        if dt <= 0.0 {
+            return;
+        }
+
+        if let Some(previous) = self.subject {
+            self.subject_velocity = (*subject - previous) / dt;
+        }
+        self.subject = Some(*subject);
+        if let Some(heading) =
+            horizontal(self.subject_velocity, self.frustum.up)
+        {
+            self.heading = heading;
+        }
+
+        // The followed point is dragged along at the edge of the dead zone.
+        let distance = (*subject - self.focus).length();
+        if distance > self.dead_zone {
+            self.focus = *subject
+                + (self.focus - *subject) * (self.dead_zone / distance);
+        }
+
+        let offset = if self.chase {
+            let up = self.frustum.up.normalize();
+            let right = up.cross(self.heading);
+
+            right * self.offset.x
+                + up * self.offset.y
+                + self.heading * self.offset.z
+        } else {
+            self.offset
+        };
+        let target = self.focus + self.subject_velocity * self.look_ahead;
+        let origin = self.focus + offset;
+
+        let (origin, origin_velocity) = spring(
+            self.frustum.origin,
+            self.origin_velocity,
+            origin,
+            self.origin_smoothing,
+            dt,
+        );
+        let (target, target_velocity) = spring(
+            self.frustum.target,
+            self.target_velocity,
+            target,
+            self.target_smoothing,
+            dt,
+        );
+
+        self.frustum.origin = origin;
+        self.frustum.target = target;
+        self.origin_velocity = origin_velocity;
+        self.target_velocity = target_velocity;
+    }
+}
+
+/// Direction of `v` in the plane orthogonal to `up`, if there is one.
+fn horizontal(
+    v: Vec3<WorldSpace>,
+    up: Vec3<WorldSpace>,
+) -> Option<Vec3<WorldSpace>> {
+    let up = up.normalize();
+    let v = v - up * v.dot(up);
+
+    if v.length() < 1e-9 {
+        None
+    } else {
+        Some(v.normalize())
+    }
+}
+
+/// Exact step of a critically damped spring, which reaches its goal in
+/// about `smoothing` seconds. Returns the new position and velocity.
+fn spring(
+    position: Point3<WorldSpace>,
+    velocity: Vec3<WorldSpace>,
+    goal: Point3<WorldSpace>,
+    smoothing: f64,
+    dt: f64,
+) -> (Point3<WorldSpace>, Vec3<WorldSpace>) {
+    if smoothing <= 0.0 {
+        return (goal, Vec3::zero());
+    }
+
+    let omega = 2.0 / smoothing;
+    let decay = (-omega * dt).exp();
+    let change = position - goal;
+    let impulse = (velocity + change * omega) * dt;
+
+    (
+        goal + (change + impulse) * decay,
+        (velocity - impulse * omega) * decay,
+    )
+}
@@ -1,16 +1,20 @@
+use crate::camera_matrices::{invert, CameraMatrices};
+use crate::spline::SplineQ;
 use crate::types::*;
 
-const UP: Vec3<WorldSpace> = Vec3::<WorldSpace>::new(0.0, 1.0, 0.0);
-
 /// Frustum struct
 #[cfg_attr(
     feature = "serialization",
     derive(serde::Serialize, serde::Deserialize)
 )]
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub struct Frustum {
     pub origin: Point3<WorldSpace>,
     pub target: Point3<WorldSpace>,
+    /// Up direction of the camera. It does not need to be orthogonal to
+    /// the view direction, but must not be parallel to it.
+    #[cfg_attr(feature = "serialization", serde(default = "default_up"))]
+    pub up: Vec3<WorldSpace>,
     pub fovy: f64,
     pub ncp: f64,
     pub fcp: f64,
@@ -19,12 +23,13 @@ pub struct Frustum {
 }
 
 impl Frustum {
+    /// The default up direction.
+    pub const UP: Vec3<WorldSpace> = Vec3::<WorldSpace>::new(0.0, 1.0, 0.0);
+
     /// Generates the view matrix.
     /// Using left hand.
     pub fn view(&self) -> Transform3<WorldSpace, CameraSpace> {
-        let f = (self.target - self.origin).normalize();
-        let s = UP.cross(f).normalize();
-        let u = f.cross(s);
+        let (s, u, f) = self.basis();
 
         Transform3::column_major(
             s.x,
@@ -46,6 +51,72 @@ impl Frustum {
         )
     }
 
+    /// Calculates the orthonormal camera basis used by the view matrix,
+    /// given as the right, up and forward direction in world space.
+    pub fn basis(
+        &self,
+    ) -> (Vec3<WorldSpace>, Vec3<WorldSpace>, Vec3<WorldSpace>) {
+        let f = (self.target - self.origin).normalize();
+        let s = self.up.cross(f).normalize();
+        let u = f.cross(s);
+
+        (s, u, f)
+    }
+
+    /// Orthonormal frame with `up` as y axis, in which orbits measure yaw
+    /// and pitch. The z axis points towards world z, or world x if `up` is
+    /// along z, so the default up direction gives the world axes.
+    pub(crate) fn up_frame(
+        &self,
+    ) -> (Vec3<WorldSpace>, Vec3<WorldSpace>, Vec3<WorldSpace>) {
+        let y = self.up.normalize();
+        let reference = if y.z.abs() < 1.0 - 1e-6 {
+            Vec3::new(0.0, 0.0, 1.0)
+        } else {
+            Vec3::new(1.0, 0.0, 0.0)
+        };
+        let z = (reference - y * reference.dot(y)).normalize();
+
+        (y.cross(z), y, z)
+    }
+
+    /// Calculates the rotation from camera space into world space, i.e.
+    /// the orientation of the camera as a quaternion.
+    pub fn orientation(&self) -> Rotation3<CameraSpace, WorldSpace> {
+        let (s, u, f) = self.basis();
+
+        // Shepperd's method on the rotation matrix with columns s, u, f.
+        let trace = s.x + u.y + f.z;
+        let (i, j, k, r) = if trace > 0.0 {
+            let t = (trace + 1.0).sqrt() * 2.0;
+            ((u.z - f.y) / t, (f.x - s.z) / t, (s.y - u.x) / t, t / 4.0)
+        } else if s.x > u.y && s.x > f.z {
+            let t = (1.0 + s.x - u.y - f.z).sqrt() * 2.0;
+            (t / 4.0, (u.x + s.y) / t, (f.x + s.z) / t, (u.z - f.y) / t)
+        } else if u.y > f.z {
+            let t = (1.0 + u.y - s.x - f.z).sqrt() * 2.0;
+            ((u.x + s.y) / t, t / 4.0, (f.y + u.z) / t, (f.x - s.z) / t)
+        } else {
+            let t = (1.0 + f.z - s.x - u.y).sqrt() * 2.0;
+            ((f.x + s.z) / t, (f.y + u.z) / t, t / 4.0, (s.y - u.x) / t)
+        };
+
+        Rotation3::quaternion(i, j, k, r).normalize()
+    }
+
+    /// Rotates the camera into the given orientation. The origin and the
+    /// distance between origin and target are kept.
+    pub fn set_orientation(
+        &mut self,
+        orientation: &Rotation3<CameraSpace, WorldSpace>,
+    ) {
+        let distance = (self.target - self.origin).length();
+        let f = orientation.transform_vector3d(Vec3::new(0.0, 0.0, 1.0));
+
+        self.target = self.origin + f * distance;
+        self.up = orientation.transform_vector3d(Vec3::new(0.0, 1.0, 0.0));
+    }
+
     /// Generates the perspective projection matrix.
     /// Using left hand with zero to one (y flip).
     pub fn projection(&self) -> Transform3<CameraSpace, ViewSpace> {
@@ -100,16 +171,9 @@ impl Frustum {
         &self,
         screen_coords: &Point2<ScreenSpace>,
     ) -> Option<(Point3<WorldSpace>, Vec3<WorldSpace>)> {
-        let view = self.view().inverse().expect("Inversing view failed.");
-        let projection = self
-            .projection()
-            .inverse()
-            .expect("Inversing projection failed.");
-        let screen = self.screen().inverse().expect("Inversing screen failed.");
-
-        let ro = screen
-            .post_transform(&projection)
-            .post_transform(&view)
+        let ro = invert(&self.screen(), "screen")
+            .post_transform(&invert(&self.projection(), "projection"))
+            .post_transform(&invert(&self.view(), "view"))
             .transform_point3d(screen_coords.to_3d())?;
 
         let rd = (ro - self.origin).normalize();
@@ -117,13 +181,169 @@ impl Frustum {
         Some((ro, rd))
     }
 
+    /// Computes all matrices and their inverses at once.
+    pub fn matrices(&self) -> CameraMatrices {
+        CameraMatrices::new(self)
+    }
+
+    /// Calculates the world space point under the given screen space
+    /// coordinate at `depth` along the view direction, measured from the
+    /// origin. Points in front of the near clipping plane are rejected.
+    pub fn point_at_depth(
+        &self,
+        screen_coords: &Point2<ScreenSpace>,
+        depth: f64,
+    ) -> Option<Point3<WorldSpace>> {
+        if depth < self.ncp {
+            return None;
+        }
+
+        let (_, rd) = self.ray_from_ncp(screen_coords)?;
+        let (_, _, f) = self.basis();
+
+        Some(self.origin + rd * (depth / rd.dot(f)))
+    }
+
+    /// Intersects the ray through the given screen space coordinate with
+    /// a plane. Intersections in front of the near clipping plane are
+    /// rejected.
+    pub fn point_on_plane(
+        &self,
+        screen_coords: &Point2<ScreenSpace>,
+        plane_point: &Point3<WorldSpace>,
+        plane_normal: &Vec3<WorldSpace>,
+    ) -> Option<Point3<WorldSpace>> {
+        let (_, rd) = self.ray_from_ncp(screen_coords)?;
+        let (_, _, f) = self.basis();
+
+        let denominator = rd.dot(*plane_normal);
+        if denominator.abs() < f64::EPSILON {
+            return None;
+        }
+
+        let t = (*plane_point - self.origin).dot(*plane_normal) / denominator;
+        if t * rd.dot(f) < self.ncp {
+            return None;
+        }
+
+        Some(self.origin + rd * t)
+    }
+
+    /// Dollies the camera towards `point` by `factor` of the distance.
+    /// The point stays under the same pixel. Negative factors move away.
+    /// The camera stops before the point passes the near clipping plane.
+    pub fn zoom_towards(&mut self, point: &Point3<WorldSpace>, factor: f64) {
+        let (_, _, f) = self.basis();
+        let offset = *point - self.origin;
+        let depth = offset.dot(f);
+        if depth <= self.ncp {
+            return;
+        }
+
+        let translation = offset * factor.min(1.0 - self.ncp / depth);
+        self.origin += translation;
+        self.target += translation;
+    }
+
+    /// Pans the camera parallel to the image plane, so that the grabbed
+    /// world space point lies under the given screen space coordinate.
+    pub fn pan_under_cursor(
+        &mut self,
+        grabbed: &Point3<WorldSpace>,
+        screen_coords: &Point2<ScreenSpace>,
+    ) {
+        let (_, _, f) = self.basis();
+        let depth = (*grabbed - self.origin).dot(f);
+
+        if let Some(point) = self.point_at_depth(screen_coords, depth) {
+            let translation = *grabbed - point;
+            self.origin += translation;
+            self.target += translation;
+        }
+    }
+
+    /// Moves the camera `delta` along its view direction and adjusts
+    /// `fovy`, so that the plane at `focal_distance` keeps its size on
+    /// screen (dolly zoom). The camera stops before the focal plane passes
+    /// the near clipping plane, and does not move forward if it already
+    /// has.
+    pub fn dolly_zoom(&mut self, focal_distance: f64, delta: f64) {
+        let (_, _, f) = self.basis();
+        let delta = delta.min((focal_distance - self.ncp).max(0.0));
+        let half_height = focal_distance * (self.fovy.to_radians() / 2.0).tan();
+
+        self.origin += f * delta;
+        self.target += f * delta;
+        self.fovy = ((half_height / (focal_distance - delta)).atan() * 2.0)
+            .to_degrees();
+    }
+
+    /// Resolves a frustum given relative to a parent frame, such as a
+    /// vehicle the camera is mounted on, into world space. `parent`
+    /// transforms from the parent frame into world space and has to be
+    /// affine. Scaling moves the camera, but keeps its field of view and
+    /// clipping planes.
+    pub fn attached_to(
+        &self,
+        parent: &Transform3<WorldSpace, WorldSpace>,
+    ) -> Frustum {
+        let point = |p| {
+            parent
+                .transform_point3d(p)
+                .expect("Fatal: Parent transform is not affine.")
+        };
+
+        Frustum {
+            origin: point(self.origin),
+            target: point(self.target),
+            up: parent.transform_vector3d(self.up),
+            ..*self
+        }
+    }
+
+    /// Blends towards `other` by `weight` in `[0, 1]`. The origin moves
+    /// on a straight line, the orientation turns along the shortest arc
+    /// and the distance to the target changes linearly. The field of view
+    /// is blended by the tangent of its half angle, so the image scale
+    /// changes evenly. The resolution switches at the end of the blend.
+    pub fn blend(&self, other: &Frustum, weight: f64) -> Frustum {
+        let weight = weight.clamp(0.0, 1.0);
+        let lerp = |a: f64, b: f64| a + (b - a) * weight;
+
+        let orientation =
+            SplineQ::slerp(&self.orientation(), &other.orientation(), weight);
+        let f = orientation.transform_vector3d(Vec3::new(0.0, 0.0, 1.0));
+        let distance = lerp(
+            (self.target - self.origin).length(),
+            (other.target - other.origin).length(),
+        );
+        let origin = self.origin.lerp(other.origin, weight);
+        let tan_half_fovy = lerp(
+            (self.fovy.to_radians() / 2.0).tan(),
+            (other.fovy.to_radians() / 2.0).tan(),
+        );
+        let last = if weight < 1.0 { self } else { other };
+
+        Frustum {
+            origin,
+            target: origin + f * distance,
+            up: orientation.transform_vector3d(Vec3::new(0.0, 1.0, 0.0)),
+            fovy: (tan_half_fovy.atan() * 2.0).to_degrees(),
+            ncp: lerp(self.ncp, other.ncp),
+            fcp: lerp(self.fcp, other.fcp),
+            width: last.width,
+            height: last.height,
+        }
+    }
+
     pub fn distance(&self, position: &Point3<WorldSpace>) -> f64 {
         (self.origin - *position).length() - self.ncp
     }
 
-    pub fn iter(&self) -> FrustumIterator {
+    pub fn iter(&self) -> FrustumIterator<'_> {
         FrustumIterator {
             frustum: self,
+            matrices: self.matrices(),
             x: 0,
             y: 0,
             width: self.width,
@@ -134,6 +354,7 @@ impl Frustum {
 
 pub struct FrustumIterator<'a> {
     pub frustum: &'a Frustum,
+    matrices: CameraMatrices,
     x: usize,
     y: usize,
     width: usize,
@@ -153,7 +374,7 @@ impl<'a> Iterator for FrustumIterator<'a> {
             None
         } else {
             let (ro, rd) = self
-                .frustum
+                .matrices
                 .ray_from_ncp(&Point2::<ScreenSpace>::new(
                     self.x as f64,
                     self.y as f64,
@@ -175,3 +396,8 @@ impl<'a> Iterator for FrustumIterator<'a> {
         )
     }
 }
+
+#[cfg(feature = "serialization")]
+fn default_up() -> Vec3<WorldSpace> {
+    Frustum::UP
+}
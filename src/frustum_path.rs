@@ -1,5 +1,12 @@
+use crate::arc_length::{ArcLength, ARC_LENGTH_TOLERANCE};
+use crate::easing::{Easing, VelocityProfile};
 use crate::frustum::Frustum;
-use crate::spline::{Spline, Spline1, Spline3};
+use crate::spline::{
+    angular_velocity, bezier_derivative, hermite_derivative, Spline, Spline1,
+    Spline3, SplineKind, SplineQ,
+};
+use crate::types::*;
+use std::f64::consts::PI;
 
 #[cfg_attr(
     feature = "serialization",
@@ -8,163 +15,1984 @@ use crate::spline::{Spline, Spline1, Spline3};
 #[derive(Default, Clone)]
 pub struct FrustumPath {
     pub key_frustums: Vec<Frustum>,
-    pub frames_per_unit: u8,
+    /// Frames per unit of path length, see `length`.
+    pub frames_per_unit: f64,
+    /// If set, the given number of frames is spread evenly over the path
+    /// and `frames_per_unit` is ignored.
+    #[cfg_attr(feature = "serialization", serde(default))]
+    pub total_frames: Option<usize>,
+    /// If set, frames are sampled in time instead of by path length and
+    /// `frames_per_unit` and `total_frames` are ignored.
+    pub timing: Option<Timing>,
+    /// Closes the path with a segment from the last key frustum back to
+    /// the first one. The control points wrap around, so the path is
+    /// smooth at the join, and the frames divide the loop evenly.
+    #[cfg_attr(feature = "serialization", serde(default))]
+    pub closed: bool,
+    #[cfg_attr(feature = "serialization", serde(default))]
+    pub orientation: OrientationMode,
+    #[cfg_attr(feature = "serialization", serde(default))]
+    pub spline: SplineKind,
+    /// Spline per segment, overriding `spline` where set.
+    #[cfg_attr(feature = "serialization", serde(default))]
+    pub segment_splines: Vec<Option<SplineKind>>,
+    /// Tangents per key frustum, required by Hermite segments.
+    #[cfg_attr(feature = "serialization", serde(default))]
+    pub key_tangents: Vec<KeyTangents>,
+    /// Handles per key frustum, required by Bezier segments.
+    #[cfg_attr(feature = "serialization", serde(default))]
+    pub key_handles: Vec<KeyHandles>,
+    /// Easing of the progress along the whole path. On a timed path it
+    /// shifts the time stamps of the inner key frustums. It is ignored if
+    /// the frames follow a `velocity_profile`, which eases them already.
+    #[cfg_attr(feature = "serialization", serde(default))]
+    pub easing: Easing,
+    /// Easing of the progress within each segment.
+    #[cfg_attr(feature = "serialization", serde(default))]
+    pub segment_easings: Vec<Option<Easing>>,
+    /// If set on a path without timing, frames are sampled in time with
+    /// the given velocity profile instead of by `frames_per_unit`. It
+    /// replaces `easing`, only `segment_easings` still apply.
+    #[cfg_attr(feature = "serialization", serde(default))]
+    pub velocity_profile: Option<VelocityProfile>,
+    /// How each scalar parameter of the key frustums is interpolated.
+    #[cfg_attr(feature = "serialization", serde(default))]
+    pub interpolation: ParameterInterpolation,
+    /// Additional per-key camera data, like lens shift or aperture.
+    #[cfg_attr(feature = "serialization", serde(default))]
+    pub channels: Vec<Channel>,
+    /// Measures the length of the path, which the frames are spaced by.
+    #[cfg_attr(feature = "serialization", serde(default))]
+    pub metric: PathMetric,
 }
 
-impl FrustumPath {
-    pub fn iter<'a>(&'a self) -> FrustumPathIterator<'a> {
-        let origins = self
-            .key_frustums
-            .iter()
-            .map(|frustum| frustum.origin)
-            .collect::<Vec<_>>();
-        let targets = self
-            .key_frustums
-            .iter()
-            .map(|frustum| frustum.target)
-            .collect::<Vec<_>>();
+/// Weights of the camera motions in the length of a path. The default is
+/// `PathMetric::TRANSLATION`. Weighting rotation and zoom gives frames to
+/// shots which turn or zoom in place.
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PathMetric {
+    /// Path units per world unit of translation.
+    pub translation: f64,
+    /// Path units per radian of rotation.
+    pub rotation: f64,
+    /// Path units per degree of change of `fovy`.
+    pub zoom: f64,
+}
+
+impl Default for PathMetric {
+    fn default() -> Self {
+        PathMetric::TRANSLATION
+    }
+}
+
+impl PathMetric {
+    /// Measures only translation, as the longer of the distances travelled
+    /// by origin and target.
+    pub const TRANSLATION: PathMetric = PathMetric {
+        translation: 1.,
+        rotation: 0.,
+        zoom: 0.,
+    };
+
+    /// Distance between two frustums. The rotation is the larger of the
+    /// angles the view and the up direction turn by, so roll counts as
+    /// well.
+    pub fn distance(&self, a: &Frustum, b: &Frustum) -> f64 {
+        let translation = (a.origin - b.origin)
+            .length()
+            .max((a.target - b.target).length());
+
+        let angle = |v: Vec3<WorldSpace>, w: Vec3<WorldSpace>| {
+            2. * ((v - w).length() / 2.).min(1.).asin()
+        };
+        let (_, ua, fa) = a.basis();
+        let (_, ub, fb) = b.basis();
+        let rotation = angle(fa, fb).max(angle(ua, ub));
+
+        self.translation * translation
+            + self.rotation * rotation
+            + self.zoom * (a.fovy - b.fovy).abs()
+    }
+}
+
+/// How a camera parameter changes between key frustums.
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub enum Interpolation {
+    /// Interpolates with the spline of the segment.
+    #[default]
+    Spline,
+    /// Keeps the value of a key frustum until the next one is reached.
+    Step,
+    /// Keeps the value of the first key frustum along the whole path.
+    Hold,
+}
+
+impl Interpolation {
+    /// Interpolates the values `v` of the four control points of a
+    /// segment at position `s`.
+    fn evaluate(
+        &self,
+        kind: &SplineKind,
+        v: (f64, f64, f64, f64),
+        first: f64,
+        s: f64,
+    ) -> f64 {
+        match self {
+            Interpolation::Spline => {
+                Spline1::spline(kind, &v.0, &v.1, &v.2, &v.3, s)
+            }
+            Interpolation::Step if s >= 1. => v.2,
+            Interpolation::Step => v.1,
+            Interpolation::Hold => first,
+        }
+    }
+}
 
-        let (_, origin_segment_lengths) = Spline3::length(&origins);
-        let (_, target_segment_lengths) = Spline3::length(&targets);
+/// Interpolation of the scalar parameters of the key frustums.
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ParameterInterpolation {
+    pub fovy: Interpolation,
+    pub ncp: Interpolation,
+    pub fcp: Interpolation,
+    /// Interpolated resolutions are rounded to whole pixels.
+    pub resolution: Interpolation,
+}
 
-        let max_segment_lengths = origin_segment_lengths
+impl Default for ParameterInterpolation {
+    fn default() -> Self {
+        ParameterInterpolation {
+            fovy: Interpolation::Spline,
+            ncp: Interpolation::Spline,
+            fcp: Interpolation::Spline,
+            resolution: Interpolation::Step,
+        }
+    }
+}
+
+/// Named camera parameter with a value for each key frustum.
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Channel {
+    pub name: String,
+    pub key_values: Vec<f64>,
+    pub interpolation: Interpolation,
+}
+
+/// Tangents of origin and target at a key frustum, in world units per
+/// segment.
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct KeyTangents {
+    pub origin: Vec3<WorldSpace>,
+    pub target: Vec3<WorldSpace>,
+}
+
+/// Bezier control points of origin and target around a key frustum.
+/// The handles towards the previous and the next key can differ to form a
+/// corner.
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct KeyHandles {
+    pub origin_in: Point3<WorldSpace>,
+    pub origin_out: Point3<WorldSpace>,
+    pub target_in: Point3<WorldSpace>,
+    pub target_out: Point3<WorldSpace>,
+}
+
+/// How the view direction and roll are interpolated between key frustums.
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub enum OrientationMode {
+    /// Interpolates the target and the up direction with splines.
+    #[default]
+    Target,
+    /// Interpolates the orientation with spherical linear interpolation.
+    /// The distance to the target is interpolated separately.
+    Slerp,
+    /// Interpolates the orientation with a smooth spherical spline
+    /// (squad). The distance to the target is interpolated separately.
+    Squad,
+}
+
+/// Time stamps for the key frustums of a path.
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+#[derive(Debug, Default, Clone)]
+pub struct Timing {
+    /// Time in seconds of each key frustum, in increasing order. Closed
+    /// paths need one more time stamp for the return to the first key.
+    pub key_times: Vec<f64>,
+    pub frames_per_second: f64,
+}
+
+impl Timing {
+    /// Number of frames in the shot. Every frame lasts
+    /// `1 / frames_per_second`, so the frames cover the time span from the
+    /// first to the last key exactly.
+    pub fn frame_count(&self) -> usize {
+        match (self.key_times.first(), self.key_times.last()) {
+            (Some(first), Some(last)) => {
+                ((last - first) * self.frames_per_second).round() as usize
+            }
+            _ => 0,
+        }
+    }
+
+    /// Panics unless there is a time stamp for every key frustum of a path
+    /// with `segment_count` segments and the time stamps increase.
+    fn check(&self, segment_count: usize) {
+        assert_eq!(
+            self.key_times.len(),
+            segment_count + 1,
+            "Every key frustum needs a time stamp."
+        );
+        assert!(
+            self.key_times.windows(2).all(|times| times[0] < times[1]),
+            "Key times must be strictly increasing."
+        );
+    }
+
+    /// Calculates the segment and the position on it for a time stamp.
+    fn segment_at(&self, time: f64) -> (usize, f64) {
+        let last_segment = self.key_times.len() - 2;
+        let segment_idx = self.key_times[1..]
             .iter()
-            .zip(target_segment_lengths.iter())
-            .map(|(o, t)| {
-                if o >= t {
-                    LongerSegment::Origin(*o)
+            .position(|key_time| time < *key_time)
+            .unwrap_or(last_segment)
+            .min(last_segment);
+
+        let start = self.key_times[segment_idx];
+        let end = self.key_times[segment_idx + 1];
+
+        (segment_idx, ((time - start) / (end - start)).clamp(0., 1.))
+    }
+}
+
+/// Number of key frustums used to approximate the dolly zoom.
+const DOLLY_ZOOM_KEYS: usize = 9;
+
+/// Key frustums per full turn of turntables and helices.
+const TURN_KEYS: usize = 8;
+
+/// Samples per segment to search for the closest point on a path.
+const CLOSEST_SAMPLES: usize = 32;
+
+/// Steps per segment to carry the rotation-minimizing frame along a path.
+const RMF_STEPS: usize = 64;
+
+/// Step of the central differences for derivatives with respect to path
+/// parameters.
+const DERIVATIVE_STEP: f64 = 1e-4;
+
+impl FrustumPath {
+    /// Generates a dolly zoom, starting at `frustum` and moving the camera
+    /// `delta` along its view direction, while the plane at
+    /// `focal_distance` keeps its size on screen.
+    pub fn dolly_zoom(
+        frustum: &Frustum,
+        focal_distance: f64,
+        delta: f64,
+        frames_per_unit: f64,
+    ) -> FrustumPath {
+        let step = delta / (DOLLY_ZOOM_KEYS - 1) as f64;
+        let key_frustums = (0..DOLLY_ZOOM_KEYS)
+            .map(|i| {
+                let mut key = *frustum;
+                key.dolly_zoom(focal_distance, step * i as f64);
+                key
+            })
+            .collect();
+
+        FrustumPath {
+            key_frustums,
+            frames_per_unit,
+            ..Default::default()
+        }
+    }
+
+    /// Generates a turntable, a closed orbit around `target` at `radius`
+    /// and `elevation` in degrees above the plane perpendicular to the up
+    /// direction of `frustum`. The camera keeps looking at `target` and
+    /// starts on the side of the origin of `frustum`, whose other
+    /// parameters are kept.
+    pub fn turntable(
+        frustum: &Frustum,
+        target: Point3<WorldSpace>,
+        radius: f64,
+        elevation: f64,
+        frames_per_unit: f64,
+    ) -> FrustumPath {
+        let mut path =
+            FrustumPath::orbit(frustum, target, radius, elevation, 1., 0.);
+        path.key_frustums.pop();
+        path.key_tangents.pop();
+
+        FrustumPath {
+            closed: true,
+            frames_per_unit,
+            ..path
+        }
+    }
+
+    /// Generates a helix of `turns` around the axis through `target` along
+    /// the up direction of `frustum`, rising by `rise` at `radius` from the
+    /// axis. The camera looks at the axis at its own height and starts on
+    /// the side of the origin of `frustum`, whose other parameters are
+    /// kept. Negative turns circle in the opposite direction.
+    pub fn helix(
+        frustum: &Frustum,
+        target: Point3<WorldSpace>,
+        radius: f64,
+        turns: f64,
+        rise: f64,
+        frames_per_unit: f64,
+    ) -> FrustumPath {
+        FrustumPath {
+            frames_per_unit,
+            ..FrustumPath::orbit(frustum, target, radius, 0., turns, rise)
+        }
+    }
+
+    /// Generates a crane shot, raising the camera by `height` along its up
+    /// direction while it keeps looking at its target.
+    pub fn crane(
+        frustum: &Frustum,
+        height: f64,
+        frames_per_unit: f64,
+    ) -> FrustumPath {
+        let mut key = *frustum;
+        key.origin += frustum.up.normalize() * height;
+
+        FrustumPath {
+            key_frustums: vec![*frustum, key],
+            frames_per_unit,
+            ..Default::default()
+        }
+    }
+
+    /// Generates a dolly-in, moving the camera `distance` along its view
+    /// direction. Negative distances dolly out.
+    pub fn dolly_in(
+        frustum: &Frustum,
+        distance: f64,
+        frames_per_unit: f64,
+    ) -> FrustumPath {
+        let (_, _, f) = frustum.basis();
+        let mut key = *frustum;
+        key.origin += f * distance;
+        key.target += f * distance;
+
+        FrustumPath {
+            key_frustums: vec![*frustum, key],
+            frames_per_unit,
+            ..Default::default()
+        }
+    }
+
+    /// Generates a flythrough along a centripetal spline through
+    /// `waypoints`. At every waypoint the camera looks at the point
+    /// `look_ahead` further along the path, beyond the last waypoint it
+    /// looks straight ahead. `look_ahead` has to be positive. The other
+    /// parameters of `frustum` are kept.
+    pub fn flythrough(
+        frustum: &Frustum,
+        waypoints: &[Point3<WorldSpace>],
+        look_ahead: f64,
+        frames_per_unit: f64,
+    ) -> FrustumPath {
+        assert!(look_ahead > 0., "The look-ahead distance must be positive.");
+
+        let mut path = FrustumPath {
+            key_frustums: waypoints
+                .iter()
+                .map(|waypoint| Frustum {
+                    origin: *waypoint,
+                    target: *waypoint + (frustum.target - frustum.origin),
+                    ..*frustum
+                })
+                .collect(),
+            frames_per_unit,
+            spline: SplineKind::Centripetal,
+            ..Default::default()
+        };
+        if waypoints.len() < 2 {
+            return path;
+        }
+
+        // With targets on the origins, the segment lengths are those of the
+        // origins alone.
+        for key in path.key_frustums.iter_mut() {
+            key.target = key.origin;
+        }
+        let segment_lengths = SegmentLengths::new(&path);
+        let last_segment = path.segment_count() - 1;
+        let (velocity, _) = path.position_derivatives(last_segment, 1., 1);
+        let end_direction = if velocity.length() < f64::EPSILON {
+            (waypoints[waypoints.len() - 1] - waypoints[waypoints.len() - 2])
+                .normalize()
+        } else {
+            velocity.normalize()
+        };
+        let end = waypoints[waypoints.len() - 1];
+
+        let mut distance = 0.;
+        let targets = (0..waypoints.len())
+            .map(|idx| {
+                let ahead = distance + look_ahead;
+                if idx <= last_segment {
+                    distance += segment_lengths.segments[idx].as_value();
+                }
+
+                if ahead < segment_lengths.total {
+                    let (segment_idx, fraction) =
+                        segment_lengths.segment_at(ahead);
+                    let s = segment_lengths
+                        .position_at_fraction(segment_idx, fraction);
+                    path.positions_on_segment(segment_idx, s).0
                 } else {
-                    LongerSegment::Target(*t)
+                    end + end_direction * (ahead - segment_lengths.total)
                 }
             })
             .collect::<Vec<_>>();
 
-        let path_length =
-            max_segment_lengths.iter().map(|s| s.as_value()).sum();
+        for (key, target) in path.key_frustums.iter_mut().zip(targets) {
+            key.target = target;
+        }
+        path
+    }
+
+    /// Generates a Hermite path of `turns` around the axis through `center`
+    /// along the up direction of `frustum`, see `helix`. The tangents make each segment a close
+    /// approximation of a circular arc.
+    fn orbit(
+        frustum: &Frustum,
+        center: Point3<WorldSpace>,
+        radius: f64,
+        elevation: f64,
+        turns: f64,
+        rise: f64,
+    ) -> FrustumPath {
+        let segment_count =
+            ((turns.abs() * TURN_KEYS as f64).ceil() as usize).max(1);
+        let step = turns * 2. * PI / segment_count as f64;
+        let rise_step = rise / segment_count as f64;
+
+        let (x, y, z) = frustum.up_frame();
+        let offset = frustum.origin - center;
+        let start = offset.dot(x).atan2(offset.dot(z));
+        let (horizontal, vertical) = (
+            radius * elevation.to_radians().cos(),
+            radius * elevation.to_radians().sin(),
+        );
+        // Tangent length of a cubic which approximates an arc of `step`.
+        let tangent_length = horizontal * 4. * (step / 4.).tan();
+
+        let (key_frustums, key_tangents) = (0..=segment_count)
+            .map(|i| {
+                let yaw = start + step * i as f64;
+                let height = rise_step * i as f64;
+                let target = center + y * height;
+                let key = Frustum {
+                    origin: target
+                        + x * (horizontal * yaw.sin())
+                        + y * vertical
+                        + z * (horizontal * yaw.cos()),
+                    target,
+                    ..*frustum
+                };
+                let tangents = KeyTangents {
+                    origin: x * (tangent_length * yaw.cos()) + y * rise_step
+                        - z * (tangent_length * yaw.sin()),
+                    target: y * rise_step,
+                };
+
+                (key, tangents)
+            })
+            .unzip();
+
+        FrustumPath {
+            key_frustums,
+            spline: SplineKind::Hermite,
+            key_tangents,
+            ..Default::default()
+        }
+    }
+
+    pub fn iter(&self) -> FrustumPathIterator<'_> {
+        if let Some(timing) = &self.timing {
+            timing.check(self.segment_count());
+        }
+
+        let segment_lengths = SegmentLengths::new(self);
+        let total = segment_lengths.total;
+        let mut length_per_frame = 1. / self.frames_per_unit;
+
+        let frame_count = match (&self.timing, &self.velocity_profile) {
+            (Some(timing), _) => timing.frame_count(),
+            (None, Some(profile)) => profile.frame_count(total),
+            (None, None) => {
+                let frame_count = match self.total_frames {
+                    Some(frame_count) => frame_count,
+                    // Stretch the frames to divide the loop evenly, so it
+                    // repeats seamlessly.
+                    None if self.closed => {
+                        (total * self.frames_per_unit).round() as usize
+                    }
+                    // The length is only accurate up to the tolerance, a
+                    // path of whole units must not lose its last frame.
+                    None => {
+                        (total
+                            * self.frames_per_unit
+                            * (1. + ARC_LENGTH_TOLERANCE))
+                            as usize
+                    }
+                };
+
+                if frame_count > 0
+                    && (self.total_frames.is_some() || self.closed)
+                {
+                    length_per_frame = total / frame_count as f64;
+                }
+                frame_count
+            }
+        };
 
         FrustumPathIterator {
             frustum_path: self,
-            path_length,
-            length_per_frame: 1. / self.frames_per_unit as f64,
-            position_on_path: 0.,
-            max_segment_lengths,
+            segment_lengths,
+            length_per_frame,
+            frame_count,
+            front: 0,
+            back: frame_count,
+        }
+    }
+
+    /// Length of the path as measured by `metric`.
+    pub fn length(&self) -> f64 {
+        SegmentLengths::new(self).total
+    }
+
+    /// Number of frames produced by `iter`.
+    pub fn frame_count(&self) -> usize {
+        self.iter().len()
+    }
+
+    /// Calculates a single frame of `iter` without producing the frames
+    /// before it. Every call measures the path anew, so to look up many
+    /// frames, use `FrustumPathIterator::frame` of one `iter` instead.
+    pub fn frame(&self, index: usize) -> Option<Frustum> {
+        self.iter().frame(index)
+    }
+
+    /// Number of segments between the key frustums, including the closing
+    /// segment of a closed path.
+    pub fn segment_count(&self) -> usize {
+        match self.key_frustums.len() {
+            0 | 1 => 0,
+            len if self.closed => len,
+            len => len - 1,
+        }
+    }
+
+    /// Evaluates the path at the given parameter. Parameters outside of the
+    /// path are clamped to its ends, or wrap around on a closed path.
+    /// Returns `None` for an empty path, or for a time on a path with
+    /// neither timing nor velocity profile.
+    ///
+    /// Except for `Segment` parameters every call measures the path anew,
+    /// see `FrustumPathIterator::frustum_at` for repeated lookups.
+    pub fn frustum_at(&self, parameter: PathParameter) -> Option<Frustum> {
+        self.evaluate(parameter, &self.segment_lengths_for(parameter))
+    }
+
+    /// Evaluates a channel at the given parameter, see `frustum_at`.
+    /// Returns `None` if there is no channel with this name.
+    pub fn channel_at(
+        &self,
+        name: &str,
+        parameter: PathParameter,
+    ) -> Option<f64> {
+        let channel = self.channels.iter().find(|c| c.name == name)?;
+        assert_eq!(
+            channel.key_values.len(),
+            self.key_frustums.len(),
+            "Every key frustum needs a value in channel {}.",
+            name
+        );
+
+        match self.key_frustums.len() {
+            0 => None,
+            1 => Some(channel.key_values[0]),
+            _ => {
+                let (segment_idx, s) = self.segment_position(parameter)?;
+                let (idx0, idx1, idx2, idx3) = self.control_points(segment_idx);
+                let v = &channel.key_values;
+
+                Some(channel.interpolation.evaluate(
+                    &self.spline_for_segment(segment_idx).scalar(),
+                    (v[idx0], v[idx1], v[idx2], v[idx3]),
+                    v[0],
+                    s,
+                ))
+            }
+        }
+    }
+
+    /// The parameter a frame of `iter` is evaluated at, for example to
+    /// evaluate the channels of the frame.
+    pub fn frame_parameter(&self, index: usize) -> Option<PathParameter> {
+        self.iter().frame_parameter(index)
+    }
+
+    /// Evaluates the path at a parameter with the given segment lengths.
+    fn evaluate(
+        &self,
+        parameter: PathParameter,
+        segment_lengths: &SegmentLengths,
+    ) -> Option<Frustum> {
+        match self.key_frustums.len() {
+            0 => None,
+            1 => Some(self.key_frustums[0]),
+            _ => {
+                let (segment_idx, position_on_segment) =
+                    self.segment_position_with(parameter, segment_lengths)?;
+                Some(self.frustum_on_segment(segment_idx, position_on_segment))
+            }
+        }
+    }
+
+    /// Measures the segments if the parameter needs their lengths.
+    fn segment_lengths_for(&self, parameter: PathParameter) -> SegmentLengths {
+        match parameter {
+            PathParameter::Segment(_) => SegmentLengths::default(),
+            _ => SegmentLengths::new(self),
+        }
+    }
+
+    /// Calculates the segment and the position on it for a parameter, on a
+    /// path with at least two key frustums.
+    fn segment_position(
+        &self,
+        parameter: PathParameter,
+    ) -> Option<(usize, f64)> {
+        self.segment_position_with(
+            parameter,
+            &self.segment_lengths_for(parameter),
+        )
+    }
+
+    /// Calculates the segment and the position on it for a parameter with
+    /// the given segment lengths, see `segment_position`.
+    fn segment_position_with(
+        &self,
+        parameter: PathParameter,
+        segment_lengths: &SegmentLengths,
+    ) -> Option<(usize, f64)> {
+        let segment_count = self.segment_count();
+        let wrap = |value: f64, period: f64| {
+            if self.closed && period > 0. {
+                value.rem_euclid(period)
+            } else {
+                value
+            }
+        };
+
+        let (segment_idx, position_on_segment) = match parameter {
+            PathParameter::Segment(t) => {
+                let t = wrap(t, segment_count as f64)
+                    .max(0.)
+                    .min(segment_count as f64);
+                let segment_idx = (t as usize).min(segment_count - 1);
+                (segment_idx, t - segment_idx as f64)
+            }
+            PathParameter::Normalized(t) => {
+                let (segment_idx, fraction) = segment_lengths
+                    .segment_at(wrap(t, 1.) * segment_lengths.total);
+                (
+                    segment_idx,
+                    segment_lengths.position_at_fraction(segment_idx, fraction),
+                )
+            }
+            PathParameter::ArcLength(distance) => {
+                let (segment_idx, fraction) = segment_lengths
+                    .segment_at(wrap(distance, segment_lengths.total));
+                (
+                    segment_idx,
+                    segment_lengths.position_at_fraction(segment_idx, fraction),
+                )
+            }
+            PathParameter::Time(time) => {
+                match (&self.timing, &self.velocity_profile) {
+                    (Some(timing), _) => {
+                        timing.check(segment_count);
+                        let first = timing.key_times[0];
+                        let duration = timing.key_times[segment_count] - first;
+                        self.position_at_time(
+                            timing,
+                            segment_lengths,
+                            first + wrap(time - first, duration),
+                        )
+                    }
+                    (None, Some(profile)) => {
+                        let total = segment_lengths.total;
+                        self.position_at_distance(
+                            segment_lengths,
+                            profile.distance_at(
+                                total,
+                                wrap(time, profile.duration(total)),
+                            ),
+                        )
+                    }
+                    (None, None) => return None,
+                }
+            }
+        };
+
+        Some((segment_idx, position_on_segment))
+    }
+
+    /// Inserts a key frustum at `index`. Per-key data of the neighbouring
+    /// key, like tangents, handles and channel values, is copied to the
+    /// new key, and the per-segment data of a split segment applies to
+    /// both halves. A timed path gets a time stamp halfway between the
+    /// neighbours, which can be adjusted afterwards.
+    pub fn insert_key(&mut self, index: usize, frustum: Frustum) {
+        assert!(index <= self.key_frustums.len(), "Key index out of bounds.");
+        let neighbour = index.max(1) - 1;
+
+        if let Some(neighbour_key) = self.key_frustums.get(neighbour) {
+            let origin_offset = frustum.origin - neighbour_key.origin;
+            let target_offset = frustum.target - neighbour_key.target;
+
+            if let Some(tangents) = self.key_tangents.get(neighbour).copied() {
+                self.key_tangents.insert(index, tangents);
+            }
+            if let Some(handles) = self.key_handles.get(neighbour).copied() {
+                self.key_handles.insert(
+                    index,
+                    KeyHandles {
+                        origin_in: handles.origin_in + origin_offset,
+                        origin_out: handles.origin_out + origin_offset,
+                        target_in: handles.target_in + target_offset,
+                        target_out: handles.target_out + target_offset,
+                    },
+                );
+            }
+            for channel in self.channels.iter_mut() {
+                if let Some(value) = channel.key_values.get(neighbour).copied()
+                {
+                    channel.key_values.insert(index, value);
+                }
+            }
+        }
+
+        if let Some(timing) = self.timing.as_mut() {
+            let times = &mut timing.key_times;
+            let time = match (index.checked_sub(1), times.get(index)) {
+                (Some(before), Some(after)) => (times[before] + after) / 2.,
+                (Some(before), None) if before > 0 => {
+                    2. * times[before] - times[before - 1]
+                }
+                (None, Some(after)) if times.len() > 1 => 2. * after - times[1],
+                (Some(before), None) => times[before] + 1.,
+                (None, Some(after)) => after - 1.,
+                (None, None) => 0.,
+            };
+            times.insert(index, time);
+        }
+
+        // The segment ending at the new key is split in two, unless the key
+        // is inserted at the start.
+        let split = index.checked_sub(1);
+        insert_segment_entry(&mut self.segment_splines, index, split);
+        insert_segment_entry(&mut self.segment_easings, index, split);
+
+        self.key_frustums.insert(index, frustum);
+    }
+
+    /// Removes the key frustum at `index` together with its per-key data.
+    /// The segments around the key merge and keep the per-segment data of
+    /// the first one.
+    pub fn remove_key(&mut self, index: usize) -> Frustum {
+        let len = self.key_frustums.len();
+        assert!(index < len, "Key index out of bounds.");
+
+        if index < self.key_tangents.len() {
+            self.key_tangents.remove(index);
+        }
+        if index < self.key_handles.len() {
+            self.key_handles.remove(index);
+        }
+        for channel in self.channels.iter_mut() {
+            if index < channel.key_values.len() {
+                channel.key_values.remove(index);
+            }
+        }
+        if let Some(timing) = self.timing.as_mut() {
+            if index < timing.key_times.len() {
+                timing.key_times.remove(index);
+            }
+        }
+
+        let segment_idx = if index + 1 == len && !self.closed {
+            index.max(1) - 1
+        } else {
+            index
+        };
+        if segment_idx < self.segment_splines.len() {
+            self.segment_splines.remove(segment_idx);
+        }
+        if segment_idx < self.segment_easings.len() {
+            self.segment_easings.remove(segment_idx);
+        }
+
+        self.key_frustums.remove(index)
+    }
+
+    /// Replaces the key frustum at `index`, keeping its per-key data.
+    /// Returns the previous key frustum, and panics if there is no key at
+    /// `index`.
+    pub fn replace_key(&mut self, index: usize, frustum: Frustum) -> Frustum {
+        assert!(index < self.key_frustums.len(), "Key index out of bounds.");
+        std::mem::replace(&mut self.key_frustums[index], frustum)
+    }
+
+    /// Splits a segment by inserting a key frustum on the path at position
+    /// `s` in `[0, 1]`. Returns the index of the new key.
+    ///
+    /// The time stamp, channel values and Bezier handles of the new key
+    /// are taken from the path, and Bezier segments keep their shape
+    /// exactly. Hermite segments keep their shape as well, but turn into
+    /// two Bezier segments, since each half runs over a shorter parameter
+    /// range and needs its own tangents at the new key. Other splines pass
+    /// through the new key, but their neighbouring segments change
+    /// slightly.
+    pub fn split_segment(&mut self, segment_idx: usize, s: f64) -> usize {
+        assert!(
+            segment_idx < self.segment_count(),
+            "Segment index out of bounds."
+        );
+        let s = s.clamp(0., 1.);
+        let index = segment_idx + 1;
+        let (_, idx1, idx2, _) = self.control_points(segment_idx);
+
+        let key = self.frustum_on_segment(segment_idx, s);
+        let channel_values = self
+            .channels
+            .iter()
+            .map(|channel| {
+                self.channel_at(
+                    &channel.name,
+                    PathParameter::Segment(segment_idx as f64 + s),
+                )
+            })
+            .collect::<Vec<_>>();
+        let time = self.timing.as_ref().map(|timing| {
+            let arc_length = SegmentLengths::new(self).segments[segment_idx]
+                .arc_length()
+                .clone();
+            let fraction = if arc_length.length() > 0. {
+                arc_length.length_at(s) / arc_length.length()
+            } else {
+                s
+            };
+            let (start, end) = (
+                timing.key_times[segment_idx],
+                timing.key_times[segment_idx + 1],
+            );
+            start + (end - start) * fraction
+        });
+        // Scaled to the mean parameter range of the halves.
+        let tangents = self.key_tangents.get(idx1).map(|_| {
+            let (origin, target) = self.position_derivatives(segment_idx, s, 1);
+            KeyTangents {
+                origin: origin * 0.5,
+                target: target * 0.5,
+            }
+        });
+        let kind = self.spline_for_segment(segment_idx);
+        let handles = match kind {
+            SplineKind::Hermite => {
+                // The Bezier control points of the Hermite segment.
+                let (k1, k2) =
+                    (&self.key_frustums[idx1], &self.key_frustums[idx2]);
+                let (t1, t2) =
+                    (self.key_tangents[idx1], self.key_tangents[idx2]);
+                let origin = split_bezier(
+                    [
+                        k1.origin,
+                        k1.origin + t1.origin / 3.,
+                        k2.origin - t2.origin / 3.,
+                        k2.origin,
+                    ],
+                    s,
+                );
+                let target = split_bezier(
+                    [
+                        k1.target,
+                        k1.target + t1.target / 3.,
+                        k2.target - t2.target / 3.,
+                        k2.target,
+                    ],
+                    s,
+                );
+                Some((origin, target))
+            }
+            SplineKind::Bezier => {
+                let (k1, k2) =
+                    (&self.key_frustums[idx1], &self.key_frustums[idx2]);
+                let (h1, h2) = (self.key_handles[idx1], self.key_handles[idx2]);
+                let origin = split_bezier(
+                    [k1.origin, h1.origin_out, h2.origin_in, k2.origin],
+                    s,
+                );
+                let target = split_bezier(
+                    [k1.target, h1.target_out, h2.target_in, k2.target],
+                    s,
+                );
+                Some((origin, target))
+            }
+            _ => None,
+        };
+
+        if kind == SplineKind::Hermite {
+            if self.key_handles.is_empty() {
+                self.key_handles = self
+                    .key_frustums
+                    .iter()
+                    .zip(self.key_tangents.iter())
+                    .map(|(key, tangents)| KeyHandles {
+                        origin_in: key.origin - tangents.origin / 3.,
+                        origin_out: key.origin + tangents.origin / 3.,
+                        target_in: key.target - tangents.target / 3.,
+                        target_out: key.target + tangents.target / 3.,
+                    })
+                    .collect();
+            }
+            let segment_count = self.segment_count();
+            self.segment_splines.resize(segment_count, None);
+        }
+
+        self.insert_key(index, key);
+        if kind == SplineKind::Hermite {
+            self.segment_splines[segment_idx] = Some(SplineKind::Bezier);
+            self.segment_splines[index] = Some(SplineKind::Bezier);
+        }
+
+        for (channel, value) in self.channels.iter_mut().zip(channel_values) {
+            if let Some(value) = value {
+                channel.key_values[index] = value;
+            }
+        }
+        if let (Some(timing), Some(time)) = (self.timing.as_mut(), time) {
+            timing.key_times[index] = time;
+        }
+        if let Some(tangents) = tangents {
+            self.key_tangents[index] = tangents;
+        }
+        if let Some((origin, target)) = handles {
+            // The indices behind the new key moved by one.
+            let idx2 = if idx2 >= index { idx2 + 1 } else { idx2 };
+            self.key_handles[idx1].origin_out = origin[0];
+            self.key_handles[idx1].target_out = target[0];
+            self.key_handles[index] = KeyHandles {
+                origin_in: origin[1],
+                origin_out: origin[2],
+                target_in: target[1],
+                target_out: target[2],
+            };
+            self.key_handles[idx2].origin_in = origin[3];
+            self.key_handles[idx2].target_in = target[3];
+        }
+
+        index
+    }
+
+    /// Finds the parameter at which the origin of the path is closest to
+    /// `position`.
+    pub fn closest_to_position(
+        &self,
+        position: &Point3<WorldSpace>,
+    ) -> Option<PathParameter> {
+        self.closest(|frustum| (frustum.origin - *position).length())
+    }
+
+    /// Finds the parameter at which the path is closest to `frustum`, as
+    /// measured by `metric`.
+    pub fn closest_to_frustum(
+        &self,
+        frustum: &Frustum,
+    ) -> Option<PathParameter> {
+        self.closest(|candidate| self.metric.distance(candidate, frustum))
+    }
+
+    /// Samples every segment to find the closest sample, and refines it
+    /// with a ternary search between its neighbouring samples.
+    fn closest<F: Fn(&Frustum) -> f64>(
+        &self,
+        distance: F,
+    ) -> Option<PathParameter> {
+        match self.key_frustums.len() {
+            0 => return None,
+            1 => return Some(PathParameter::Segment(0.)),
+            _ => {}
+        }
+
+        let step = 1. / CLOSEST_SAMPLES as f64;
+        let mut closest = (f64::INFINITY, 0, 0.);
+        for segment_idx in 0..self.segment_count() {
+            for i in 0..=CLOSEST_SAMPLES {
+                let s = i as f64 * step;
+                let d = distance(&self.frustum_on_segment(segment_idx, s));
+                if d < closest.0 {
+                    closest = (d, segment_idx, s);
+                }
+            }
+        }
+
+        let (_, segment_idx, s) = closest;
+        let at = |s: f64| distance(&self.frustum_on_segment(segment_idx, s));
+        let (mut a, mut b) = ((s - step).max(0.), (s + step).min(1.));
+        for _ in 0..50 {
+            let (m1, m2) = (a + (b - a) / 3., b - (b - a) / 3.);
+            if at(m1) < at(m2) {
+                b = m2;
+            } else {
+                a = m1;
+            }
+        }
+
+        Some(PathParameter::Segment(segment_idx as f64 + (a + b) / 2.))
+    }
+
+    /// Velocity of the origin with respect to the kind of the parameter,
+    /// e.g. in world units per second for `Time` or per unit of path
+    /// length for `ArcLength`.
+    pub fn velocity(
+        &self,
+        parameter: PathParameter,
+    ) -> Option<Vec3<WorldSpace>> {
+        self.derivatives(parameter, |segment_idx, s, order| {
+            self.position_derivatives(segment_idx, s, order).0
+        })
+        .map(|(velocity, _)| velocity)
+    }
+
+    /// Acceleration of the origin with respect to the kind of the
+    /// parameter, see `velocity`.
+    pub fn acceleration(
+        &self,
+        parameter: PathParameter,
+    ) -> Option<Vec3<WorldSpace>> {
+        self.derivatives(parameter, |segment_idx, s, order| {
+            self.position_derivatives(segment_idx, s, order).0
+        })
+        .map(|(_, acceleration)| acceleration)
+    }
+
+    /// Angular velocity of the camera in world space, in radians with
+    /// respect to the kind of the parameter, see `velocity`.
+    pub fn angular_velocity(
+        &self,
+        parameter: PathParameter,
+    ) -> Option<Vec3<WorldSpace>> {
+        self.derivatives(parameter, |segment_idx, s, order| {
+            self.orientation_derivatives(segment_idx, s, order)
+        })
+        .map(|(velocity, _)| velocity)
+    }
+
+    /// Angular acceleration of the camera in world space, see
+    /// `angular_velocity`.
+    pub fn angular_acceleration(
+        &self,
+        parameter: PathParameter,
+    ) -> Option<Vec3<WorldSpace>> {
+        self.derivatives(parameter, |segment_idx, s, order| {
+            self.orientation_derivatives(segment_idx, s, order)
+        })
+        .map(|(_, acceleration)| acceleration)
+    }
+
+    /// Curvature of the path of the origin, the inverse of the radius of
+    /// its osculating circle. It is zero where the origin rests.
+    pub fn curvature(&self, parameter: PathParameter) -> Option<f64> {
+        match self.key_frustums.len() {
+            0 => return None,
+            1 => return Some(0.),
+            _ => {}
+        }
+
+        let (segment_idx, s) = self.segment_position(parameter)?;
+        let (d1, _) = self.position_derivatives(segment_idx, s, 1);
+        let (d2, _) = self.position_derivatives(segment_idx, s, 2);
+
+        let speed = d1.length();
+        if speed < f64::EPSILON {
+            return Some(0.);
+        }
+
+        Some(d1.cross(d2).length() / (speed * speed * speed))
+    }
+
+    /// Rotation-minimizing frame of the path of the origin, as tangent,
+    /// normal and binormal.
+    ///
+    /// The normal starts closest to the up direction of the first key
+    /// frustum and is carried along the path with the double reflection
+    /// method, so unlike the Frenet frame it neither twists nor flips at
+    /// inflection points.
+    pub fn rotation_minimizing_frame(
+        &self,
+        parameter: PathParameter,
+    ) -> Option<(Vec3<WorldSpace>, Vec3<WorldSpace>, Vec3<WorldSpace>)> {
+        if self.key_frustums.len() < 2 {
+            return None;
+        }
+
+        let (segment_idx, s) = self.segment_position(parameter)?;
+        let end = segment_idx as f64 + s;
+        let last_segment = self.segment_count() - 1;
+        let sample = |t: f64| {
+            let segment_idx = (t as usize).min(last_segment);
+            let s = t - segment_idx as f64;
+            (
+                self.position_derivatives(segment_idx, s, 0).0,
+                self.position_derivatives(segment_idx, s, 1).0,
+            )
+        };
+        let direction = |velocity: Vec3<WorldSpace>, fallback| {
+            if velocity.length() < f64::EPSILON {
+                fallback
+            } else {
+                velocity.normalize()
+            }
+        };
+
+        let (mut position, velocity) = sample(0.);
+        let first = &self.key_frustums[0];
+        let mut tangent = direction(velocity, first.basis().2);
+        let mut normal = first.up - tangent * first.up.dot(tangent);
+        if normal.length() < f64::EPSILON {
+            normal = first.basis().1;
+        }
+        normal = normal.normalize();
+
+        let steps = ((end * RMF_STEPS as f64).ceil() as usize).max(1);
+        for i in 1..=steps {
+            let (next_position, velocity) =
+                sample(end * i as f64 / steps as f64);
+            let next_tangent = direction(velocity, tangent);
+
+            // Reflect the frame at the bisector plane of the step, then
+            // at the plane which maps the tangent onto the next tangent.
+            let v1 = next_position - position;
+            let c1 = v1.dot(v1);
+            if c1 > f64::EPSILON * f64::EPSILON {
+                let normal_l = normal - v1 * (2. / c1 * v1.dot(normal));
+                let tangent_l = tangent - v1 * (2. / c1 * v1.dot(tangent));
+                let v2 = next_tangent - tangent_l;
+                let c2 = v2.dot(v2);
+                normal = if c2 > f64::EPSILON * f64::EPSILON {
+                    normal_l - v2 * (2. / c2 * v2.dot(normal_l))
+                } else {
+                    normal_l
+                };
+            }
+
+            normal =
+                (normal - next_tangent * normal.dot(next_tangent)).normalize();
+            position = next_position;
+            tangent = next_tangent;
+        }
+
+        Some((tangent, normal, tangent.cross(normal)))
+    }
+
+    /// Applies the chain rule to the derivatives of a quantity with respect
+    /// to the spline position, given by `derivative` for orders 1 and 2, to
+    /// get its derivatives with respect to the kind of the parameter.
+    fn derivatives<F>(
+        &self,
+        parameter: PathParameter,
+        derivative: F,
+    ) -> Option<(Vec3<WorldSpace>, Vec3<WorldSpace>)>
+    where
+        F: Fn(usize, f64, usize) -> Vec3<WorldSpace>,
+    {
+        match self.key_frustums.len() {
+            0 => return None,
+            1 => return Some((Vec3::zero(), Vec3::zero())),
+            _ => {}
+        }
+
+        let (segment_idx, s) = self.segment_position(parameter)?;
+        let (rate, rate_derivative) =
+            self.position_rates(parameter, segment_idx, s)?;
+        let d1 = derivative(segment_idx, s, 1);
+        let d2 = derivative(segment_idx, s, 2);
+
+        Some((d1 * rate, d2 * (rate * rate) + d1 * rate_derivative))
+    }
+
+    /// First and second derivative of the spline position `segment_idx + s`
+    /// with respect to the parameter. The mapping is not polynomial for
+    /// most parameters, so the derivatives are calculated numerically.
+    fn position_rates(
+        &self,
+        parameter: PathParameter,
+        segment_idx: usize,
+        s: f64,
+    ) -> Option<(f64, f64)> {
+        let (value, kind): (f64, fn(f64) -> PathParameter) = match parameter {
+            PathParameter::Segment(_) => return Some((1., 0.)),
+            PathParameter::Normalized(t) => (t, PathParameter::Normalized),
+            PathParameter::ArcLength(d) => (d, PathParameter::ArcLength),
+            PathParameter::Time(t) => (t, PathParameter::Time),
+        };
+
+        let center = segment_idx as f64 + s;
+        let segment_count = self.segment_count() as f64;
+        let offset = |value: f64| {
+            let (segment_idx, s) = self.segment_position(kind(value))?;
+            let offset = segment_idx as f64 + s - center;
+            // Closed paths jump back to the first segment at the join.
+            if self.closed && offset.abs() > segment_count / 2. {
+                Some(offset - segment_count * offset.signum())
+            } else {
+                Some(offset)
+            }
+        };
+
+        let h = DERIVATIVE_STEP;
+        let before = offset(value - h)?;
+        let after = offset(value + h)?;
+
+        // One sided at the ends of an open path, where the parameter is
+        // clamped.
+        Some(if before == 0. {
+            (after / h, 0.)
+        } else if after == 0. {
+            (-before / h, 0.)
+        } else {
+            ((after - before) / (2. * h), (after + before) / (h * h))
+        })
+    }
+
+    /// Derivatives of the given order of the camera orientation with
+    /// respect to `s`, as angular velocity and acceleration.
+    fn orientation_derivatives(
+        &self,
+        segment_idx: usize,
+        s: f64,
+        order: usize,
+    ) -> Vec3<WorldSpace> {
+        if self.orientation == OrientationMode::Squad {
+            let (idx0, idx1, idx2, idx3) = self.control_points(segment_idx);
+            let kind = self.spline_for_segment(segment_idx);
+            let (q0, q1, q2, q3) = (
+                &self.key_frustums[idx0].orientation(),
+                &self.key_frustums[idx1].orientation(),
+                &self.key_frustums[idx2].orientation(),
+                &self.key_frustums[idx3].orientation(),
+            );
+
+            return if order == 1 {
+                SplineQ::spline_derivative(&kind, q0, q1, q2, q3, s)
+            } else {
+                SplineQ::spline_second_derivative(&kind, q0, q1, q2, q3, s)
+            };
+        }
+
+        let h = DERIVATIVE_STEP;
+        let velocity = |s: f64| {
+            angular_velocity(
+                &self.frustum_on_segment(segment_idx, s - h).orientation(),
+                &self.frustum_on_segment(segment_idx, s + h).orientation(),
+                2. * h,
+            )
+        };
+
+        if order == 1 {
+            velocity(s)
+        } else {
+            (velocity(s + h) - velocity(s - h)) / (2. * h)
+        }
+    }
+
+    /// The spline family used for a segment.
+    pub fn spline_for_segment(&self, segment_idx: usize) -> SplineKind {
+        self.segment_splines
+            .get(segment_idx)
+            .copied()
+            .flatten()
+            .unwrap_or(self.spline)
+    }
+
+    /// Calculates the eased segment and position on it for a distance
+    /// along the path.
+    fn position_at_distance(
+        &self,
+        segment_lengths: &SegmentLengths,
+        distance: f64,
+    ) -> (usize, f64) {
+        let total = segment_lengths.total;
+        let distance = match self.easing {
+            // A velocity profile eases the frames already.
+            _ if self.velocity_profile.is_some() => distance,
+            Easing::Linear => distance,
+            easing if total > 0. => easing.apply(distance / total) * total,
+            _ => distance,
+        };
+
+        let (segment_idx, fraction) = segment_lengths.segment_at(distance);
+        (
+            segment_idx,
+            self.eased_position_on_segment(
+                segment_lengths,
+                segment_idx,
+                fraction,
+            ),
+        )
+    }
+
+    /// Calculates the eased segment and position on it for a time stamp.
+    fn position_at_time(
+        &self,
+        timing: &Timing,
+        segment_lengths: &SegmentLengths,
+        time: f64,
+    ) -> (usize, f64) {
+        let first = timing.key_times[0];
+        let duration = timing.key_times[timing.key_times.len() - 1] - first;
+        let time = match self.easing {
+            Easing::Linear => time,
+            easing if duration > 0. => {
+                first + easing.apply((time - first) / duration) * duration
+            }
+            _ => time,
+        };
+
+        let (segment_idx, fraction) = timing.segment_at(time);
+        (
+            segment_idx,
+            self.eased_position_on_segment(
+                segment_lengths,
+                segment_idx,
+                fraction,
+            ),
+        )
+    }
+
+    /// Applies the easing of a segment to the fraction of its length and
+    /// maps it to the spline position.
+    fn eased_position_on_segment(
+        &self,
+        segment_lengths: &SegmentLengths,
+        segment_idx: usize,
+        fraction: f64,
+    ) -> f64 {
+        let fraction = match self.segment_easings.get(segment_idx) {
+            Some(Some(easing)) => easing.apply(fraction),
+            _ => fraction,
+        };
+
+        segment_lengths.position_at_fraction(segment_idx, fraction)
+    }
+
+    /// Indices of the key frustums controlling a segment.
+    fn control_points(
+        &self,
+        segment_idx: usize,
+    ) -> (usize, usize, usize, usize) {
+        if self.closed {
+            SplineQ::get_control_points_for_closed_segment(
+                segment_idx,
+                self.key_frustums.len(),
+            )
+        } else {
+            SplineQ::get_control_points_for_segment(
+                segment_idx,
+                self.key_frustums.len(),
+            )
+        }
+    }
+
+    /// Interpolates origin and target on a segment at position `s` in
+    /// `[0, 1]`.
+    fn positions_on_segment(
+        &self,
+        segment_idx: usize,
+        s: f64,
+    ) -> (Point3<WorldSpace>, Point3<WorldSpace>) {
+        let (origin, target) = self.position_derivatives(segment_idx, s, 0);
+        (origin.to_point(), target.to_point())
+    }
+
+    /// Derivatives of the given order of origin and target with respect to
+    /// `s`, where order 0 gives the positions as vectors.
+    fn position_derivatives(
+        &self,
+        segment_idx: usize,
+        s: f64,
+        order: usize,
+    ) -> (Vec3<WorldSpace>, Vec3<WorldSpace>) {
+        let (idx0, idx1, idx2, idx3) = self.control_points(segment_idx);
+        let (k0, k1, k2, k3) = (
+            &self.key_frustums[idx0],
+            &self.key_frustums[idx1],
+            &self.key_frustums[idx2],
+            &self.key_frustums[idx3],
+        );
+
+        match self.spline_for_segment(segment_idx) {
+            SplineKind::Hermite => {
+                let (t1, t2) = (
+                    self.key_tangents.get(idx1).expect(
+                        "Hermite segments need tangents for every key.",
+                    ),
+                    self.key_tangents.get(idx2).expect(
+                        "Hermite segments need tangents for every key.",
+                    ),
+                );
+
+                (
+                    hermite_derivative(
+                        k1.origin.to_vector(),
+                        t1.origin,
+                        k2.origin.to_vector(),
+                        t2.origin,
+                        s,
+                        order,
+                    ),
+                    hermite_derivative(
+                        k1.target.to_vector(),
+                        t1.target,
+                        k2.target.to_vector(),
+                        t2.target,
+                        s,
+                        order,
+                    ),
+                )
+            }
+            SplineKind::Bezier => {
+                let (h1, h2) = (
+                    self.key_handles
+                        .get(idx1)
+                        .expect("Bezier segments need handles for every key."),
+                    self.key_handles
+                        .get(idx2)
+                        .expect("Bezier segments need handles for every key."),
+                );
+
+                (
+                    bezier_derivative(
+                        k1.origin.to_vector(),
+                        h1.origin_out.to_vector(),
+                        h2.origin_in.to_vector(),
+                        k2.origin.to_vector(),
+                        s,
+                        order,
+                    ),
+                    bezier_derivative(
+                        k1.target.to_vector(),
+                        h1.target_out.to_vector(),
+                        h2.target_in.to_vector(),
+                        k2.target.to_vector(),
+                        s,
+                        order,
+                    ),
+                )
+            }
+            kind => {
+                let spline = |value: fn(&Frustum) -> Point3<WorldSpace>| {
+                    let (v0, v1, v2, v3) =
+                        (&value(k0), &value(k1), &value(k2), &value(k3));
+                    match order {
+                        0 => Spline3::spline(&kind, v0, v1, v2, v3, s)
+                            .to_vector(),
+                        1 => {
+                            Spline3::spline_derivative(&kind, v0, v1, v2, v3, s)
+                        }
+                        _ => Spline3::spline_second_derivative(
+                            &kind, v0, v1, v2, v3, s,
+                        ),
+                    }
+                };
+
+                (spline(|k| k.origin), spline(|k| k.target))
+            }
+        }
+    }
+
+    /// Interpolates the key frustums on a segment at position `s` in
+    /// `[0, 1]`.
+    fn frustum_on_segment(&self, segment_idx: usize, s: f64) -> Frustum {
+        let kind = self.spline_for_segment(segment_idx).scalar();
+        let (idx0, idx1, idx2, idx3) = self.control_points(segment_idx);
+        let (k0, k1, k2, k3) = (
+            &self.key_frustums[idx0],
+            &self.key_frustums[idx1],
+            &self.key_frustums[idx2],
+            &self.key_frustums[idx3],
+        );
+        let scalar = |interpolation: Interpolation,
+                      value: fn(&Frustum) -> f64| {
+            interpolation.evaluate(
+                &kind,
+                (value(k0), value(k1), value(k2), value(k3)),
+                value(&self.key_frustums[0]),
+                s,
+            )
+        };
+        let interpolation = &self.interpolation;
+
+        let (origin, target) = self.positions_on_segment(segment_idx, s);
+
+        let (target, up) = match self.orientation {
+            OrientationMode::Target => {
+                let up =
+                    kind.evaluate(k0.up, k1.up, k2.up, k3.up, s).normalize();
+
+                (target, up)
+            }
+            mode => {
+                let orientation = if mode == OrientationMode::Slerp {
+                    SplineQ::slerp(&k1.orientation(), &k2.orientation(), s)
+                } else {
+                    SplineQ::catmull_rom(
+                        &k0.orientation(),
+                        &k1.orientation(),
+                        &k2.orientation(),
+                        &k3.orientation(),
+                        s,
+                    )
+                };
+
+                let distance = scalar(Interpolation::Spline, |k| {
+                    (k.target - k.origin).length()
+                });
+
+                let f = orientation.transform_vector3d(Vec3::new(0., 0., 1.));
+                let up = orientation.transform_vector3d(Vec3::new(0., 1., 0.));
+
+                (origin + f * distance, up)
+            }
+        };
+
+        Frustum {
+            origin,
+            target,
+            up,
+            fovy: scalar(interpolation.fovy, |k| k.fovy),
+            ncp: scalar(interpolation.ncp, |k| k.ncp),
+            fcp: scalar(interpolation.fcp, |k| k.fcp),
+            width: scalar(interpolation.resolution, |k| k.width as f64).round()
+                as usize,
+            height: scalar(interpolation.resolution, |k| k.height as f64)
+                .round() as usize,
         }
     }
 }
 
+/// Parameter to evaluate a path at.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum PathParameter {
+    /// Spline parameter, where the integer part selects the segment and
+    /// the fractional part the position on it.
+    Segment(f64),
+    /// Fraction of the path length in `[0, 1]`.
+    Normalized(f64),
+    /// Distance along the path, see `FrustumPath::length`.
+    ArcLength(f64),
+    /// Time in seconds. Requires the path to have a timing or a velocity
+    /// profile.
+    Time(f64),
+}
+
 enum LongerSegment {
-    Origin(f64),
-    Target(f64),
+    Origin(ArcLength),
+    Target(ArcLength),
+    /// Measured with a metric which weighs rotation or zoom.
+    Combined(ArcLength),
 }
 
 impl LongerSegment {
-    fn as_value(&self) -> &f64 {
+    fn arc_length(&self) -> &ArcLength {
         match self {
             Self::Origin(v) => v,
             Self::Target(v) => v,
+            Self::Combined(v) => v,
         }
     }
+
+    fn as_value(&self) -> f64 {
+        self.arc_length().length()
+    }
 }
 
+#[derive(Default)]
+struct SegmentLengths {
+    segments: Vec<LongerSegment>,
+    total: f64,
+}
+
+impl SegmentLengths {
+    fn new(frustum_path: &FrustumPath) -> Self {
+        let key_frustums = &frustum_path.key_frustums;
+        if key_frustums.len() < 2 {
+            return SegmentLengths {
+                segments: Vec::new(),
+                total: 0.,
+            };
+        }
+
+        let origins = key_frustums
+            .iter()
+            .map(|frustum| frustum.origin)
+            .collect::<Vec<_>>();
+        let targets = key_frustums
+            .iter()
+            .map(|frustum| frustum.target)
+            .collect::<Vec<_>>();
+
+        let segments = (0..frustum_path.segment_count())
+            .map(|segment_idx| {
+                if frustum_path.metric != PathMetric::TRANSLATION {
+                    return LongerSegment::Combined(ArcLength::new(
+                        |s| frustum_path.frustum_on_segment(segment_idx, s),
+                        |a, b| frustum_path.metric.distance(a, b),
+                    ));
+                }
+
+                let kind = frustum_path.spline_for_segment(segment_idx);
+
+                // The shape depends on explicit tangents or handles, or on
+                // control points wrapping around a closed path.
+                let (o, t) = if frustum_path.closed
+                    || matches!(kind, SplineKind::Hermite | SplineKind::Bezier)
+                {
+                    (
+                        ArcLength::new(
+                            |s| {
+                                frustum_path
+                                    .positions_on_segment(segment_idx, s)
+                                    .0
+                            },
+                            |a, b| (*a - *b).length(),
+                        ),
+                        ArcLength::new(
+                            |s| {
+                                frustum_path
+                                    .positions_on_segment(segment_idx, s)
+                                    .1
+                            },
+                            |a, b| (*a - *b).length(),
+                        ),
+                    )
+                } else {
+                    (
+                        Spline3::length_for_segment(
+                            &kind,
+                            &origins,
+                            segment_idx,
+                        ),
+                        Spline3::length_for_segment(
+                            &kind,
+                            &targets,
+                            segment_idx,
+                        ),
+                    )
+                };
+
+                if o.length() >= t.length() {
+                    LongerSegment::Origin(o)
+                } else {
+                    LongerSegment::Target(t)
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let total = segments.iter().map(|s| s.as_value()).sum();
+
+        SegmentLengths { segments, total }
+    }
+
+    /// Calculates the segment and the fraction of its length for a distance
+    /// along the path.
+    fn segment_at(&self, distance: f64) -> (usize, f64) {
+        let mut start = 0.;
+
+        for (segment_idx, segment) in self.segments.iter().enumerate() {
+            let length = segment.as_value();
+
+            if length > 0. && distance <= start + length {
+                return (segment_idx, ((distance - start) / length).max(0.));
+            }
+            start += length;
+        }
+
+        (self.segments.len().max(1) - 1, 1.)
+    }
+
+    /// Calculates the position on a segment after the given fraction of
+    /// its length. The arc-length table of the segment maps the length to
+    /// the spline position, so equal distances are equally long on the
+    /// curve. Segments without length are traversed linearly.
+    fn position_at_fraction(&self, segment_idx: usize, fraction: f64) -> f64 {
+        let arc_length = self.segments[segment_idx].arc_length();
+
+        if arc_length.length() > 0. {
+            arc_length.parameter_at(fraction * arc_length.length())
+        } else {
+            fraction
+        }
+    }
+}
+
+/// Iterator over the frames of a path.
+///
+/// It keeps the measured segments of the path, so it also serves as a
+/// sampler for random access to frames and parameters, which can be
+/// shared between threads.
 pub struct FrustumPathIterator<'a> {
     frustum_path: &'a FrustumPath,
-    path_length: f64,
+    segment_lengths: SegmentLengths,
     length_per_frame: f64,
-    max_segment_lengths: Vec<LongerSegment>,
-    position_on_path: f64,
+    frame_count: usize,
+    front: usize,
+    back: usize,
 }
 
-impl<'a> FrustumPathIterator<'a> {}
+impl<'a> FrustumPathIterator<'a> {
+    /// Calculates the frame at `index` of the whole sequence, regardless
+    /// of how far the iterator has advanced.
+    pub fn frame(&self, index: usize) -> Option<Frustum> {
+        if index < self.frame_count {
+            Some(self.evaluate(index))
+        } else {
+            None
+        }
+    }
+
+    /// Evaluates the path at the given parameter, see
+    /// `FrustumPath::frustum_at`.
+    pub fn frustum_at(&self, parameter: PathParameter) -> Option<Frustum> {
+        self.frustum_path.evaluate(parameter, &self.segment_lengths)
+    }
+
+    /// The parameter the frame at `index` is evaluated at, see
+    /// `FrustumPath::frame_parameter`.
+    pub fn frame_parameter(&self, index: usize) -> Option<PathParameter> {
+        if index >= self.frame_count {
+            return None;
+        }
+
+        let (segment_idx, position_on_segment) = self.position(index);
+        Some(PathParameter::Segment(
+            segment_idx as f64 + position_on_segment,
+        ))
+    }
+
+    /// Resolves the frames of a path given relative to a moving parent
+    /// frame into world space. `parent` maps the index of a frame to the
+    /// transform of the parent frame into world space at that frame, see
+    /// `Frustum::attached_to`.
+    pub fn attached_to<F>(self, parent: F) -> AttachedIterator<'a, F>
+    where
+        F: FnMut(usize) -> Transform3<WorldSpace, WorldSpace>,
+    {
+        AttachedIterator {
+            frames: self,
+            parent,
+        }
+    }
+
+    /// Calculates the segment and the position on it of a frame.
+    fn position(&self, index: usize) -> (usize, f64) {
+        let path = self.frustum_path;
+        let lengths = &self.segment_lengths;
+
+        match (&path.timing, &path.velocity_profile) {
+            (Some(timing), _) => path.position_at_time(
+                timing,
+                lengths,
+                timing.key_times[0] + index as f64 / timing.frames_per_second,
+            ),
+            (None, Some(profile)) => path.position_at_distance(
+                lengths,
+                profile.distance_at(
+                    lengths.total,
+                    index as f64 / profile.frames_per_second,
+                ),
+            ),
+            (None, None) => path.position_at_distance(
+                lengths,
+                (index + 1) as f64 * self.length_per_frame,
+            ),
+        }
+    }
+
+    fn evaluate(&self, index: usize) -> Frustum {
+        let (segment_idx, position_on_segment) = self.position(index);
+
+        self.frustum_path
+            .frustum_on_segment(segment_idx, position_on_segment)
+    }
+}
 
 impl<'a> Iterator for FrustumPathIterator<'a> {
     type Item = Frustum;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let mut segment_idx = self.position_on_path as usize;
-        let mut position_on_segment = self.position_on_path.fract();
+        if self.front >= self.back {
+            return None;
+        }
 
-        let mut remaining = self.length_per_frame;
+        let frustum = self.evaluate(self.front);
+        self.front += 1;
 
-        for segment_length in self.max_segment_lengths[segment_idx..].iter() {
-            let remaining_on_segment = segment_length.as_value()
-                - segment_length.as_value() * position_on_segment;
+        Some(frustum)
+    }
 
-            if remaining_on_segment >= remaining {
-                position_on_segment += remaining / segment_length.as_value();
-                break;
-            } else {
-                remaining -= remaining_on_segment;
-                segment_idx += 1;
-                position_on_segment = 0.;
-            }
-        }
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.front = self.front.saturating_add(n).min(self.back);
+        self.next()
+    }
 
-        self.position_on_path = segment_idx as f64 + position_on_segment;
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.back - self.front;
+        (remaining, Some(remaining))
+    }
+}
 
-        if segment_idx > self.max_segment_lengths.len() - 1 {
+impl<'a> DoubleEndedIterator for FrustumPathIterator<'a> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.front >= self.back {
             return None;
         }
 
-        let (idx0, idx1, idx2, idx3) = Spline3::get_control_points_for_segment(
-            segment_idx,
-            self.frustum_path.key_frustums.len(),
-        );
+        self.back -= 1;
 
-        let origin = Spline3::catmull_rom(
-            &self.frustum_path.key_frustums[idx0].origin,
-            &self.frustum_path.key_frustums[idx1].origin,
-            &self.frustum_path.key_frustums[idx2].origin,
-            &self.frustum_path.key_frustums[idx3].origin,
-            position_on_segment,
-        );
+        Some(self.evaluate(self.back))
+    }
 
-        let target = Spline3::catmull_rom(
-            &self.frustum_path.key_frustums[idx0].target,
-            &self.frustum_path.key_frustums[idx1].target,
-            &self.frustum_path.key_frustums[idx2].target,
-            &self.frustum_path.key_frustums[idx3].target,
-            position_on_segment,
-        );
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        self.back = self.back.saturating_sub(n).max(self.front);
+        self.next_back()
+    }
+}
 
-        let fovy = Spline1::catmull_rom(
-            &self.frustum_path.key_frustums[idx0].fovy,
-            &self.frustum_path.key_frustums[idx1].fovy,
-            &self.frustum_path.key_frustums[idx2].fovy,
-            &self.frustum_path.key_frustums[idx3].fovy,
-            position_on_segment,
-        );
+impl<'a> ExactSizeIterator for FrustumPathIterator<'a> {}
 
-        let ncp = Spline1::catmull_rom(
-            &self.frustum_path.key_frustums[idx0].ncp,
-            &self.frustum_path.key_frustums[idx1].ncp,
-            &self.frustum_path.key_frustums[idx2].ncp,
-            &self.frustum_path.key_frustums[idx3].ncp,
-            position_on_segment,
-        );
+/// Iterator over the frames of a path attached to a moving parent frame,
+/// see `FrustumPathIterator::attached_to`.
+pub struct AttachedIterator<'a, F> {
+    frames: FrustumPathIterator<'a>,
+    parent: F,
+}
 
-        let fcp = Spline1::catmull_rom(
-            &self.frustum_path.key_frustums[idx0].fcp,
-            &self.frustum_path.key_frustums[idx1].fcp,
-            &self.frustum_path.key_frustums[idx2].fcp,
-            &self.frustum_path.key_frustums[idx3].fcp,
-            position_on_segment,
-        );
+impl<'a, F> Iterator for AttachedIterator<'a, F>
+where
+    F: FnMut(usize) -> Transform3<WorldSpace, WorldSpace>,
+{
+    type Item = Frustum;
 
-        Some(Frustum {
-            origin,
-            target,
-            fovy,
-            ncp,
-            fcp,
-            width: self.frustum_path.key_frustums[0].width,
-            height: self.frustum_path.key_frustums[0].height,
-        })
+    fn next(&mut self) -> Option<Self::Item> {
+        let index = self.frames.front;
+        let frustum = self.frames.next()?;
+
+        Some(frustum.attached_to(&(self.parent)(index)))
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        let frustum = self.frames.nth(n)?;
+        let index = self.frames.front - 1;
+
+        Some(frustum.attached_to(&(self.parent)(index)))
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
-        (
-            (self.path_length / self.length_per_frame) as usize,
-            Some((self.path_length / self.length_per_frame).ceil() as usize),
-        )
+        self.frames.size_hint()
+    }
+}
+
+impl<'a, F> DoubleEndedIterator for AttachedIterator<'a, F>
+where
+    F: FnMut(usize) -> Transform3<WorldSpace, WorldSpace>,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let frustum = self.frames.next_back()?;
+        let index = self.frames.back;
+
+        Some(frustum.attached_to(&(self.parent)(index)))
+    }
+
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        let frustum = self.frames.nth_back(n)?;
+        let index = self.frames.back;
+
+        Some(frustum.attached_to(&(self.parent)(index)))
+    }
+}
+
+impl<'a, F> ExactSizeIterator for AttachedIterator<'a, F> where
+    F: FnMut(usize) -> Transform3<WorldSpace, WorldSpace>
+{
+}
+
+/// Inserts an entry for a new segment into per-segment data, copying the
+/// entry of the segment it was split from.
+fn insert_segment_entry<T: Copy>(
+    values: &mut Vec<Option<T>>,
+    segment_idx: usize,
+    split_from: Option<usize>,
+) {
+    if segment_idx <= values.len() && !values.is_empty() {
+        let value =
+            split_from.and_then(|idx| values.get(idx).copied().flatten());
+        values.insert(segment_idx, value);
     }
 }
+
+/// Splits a cubic Bezier curve with de Casteljau's algorithm. Returns the
+/// inner control points of both halves.
+fn split_bezier(
+    points: [Point3<WorldSpace>; 4],
+    s: f64,
+) -> [Point3<WorldSpace>; 4] {
+    let [p0, p1, p2, p3] = points;
+    let p01 = p0.lerp(p1, s);
+    let p12 = p1.lerp(p2, s);
+    let p23 = p2.lerp(p3, s);
+    let p012 = p01.lerp(p12, s);
+    let p123 = p12.lerp(p23, s);
+
+    [p01, p012, p123, p23]
+}
@@ -1,11 +1,42 @@
 mod types;
 pub use crate::types::*;
+mod arc_length;
+mod blend;
 
+mod camera_matrices;
+mod easing;
+mod fit;
 mod frustum;
 
+pub use crate::blend::BlendIterator;
+pub use crate::camera_matrices::CameraMatrices;
+pub use crate::easing::{Easing, VelocityProfile};
 pub use crate::frustum::Frustum;
 
 mod frustum_path;
+mod shake;
 mod spline;
 
-pub use crate::frustum_path::FrustumPath;
+pub use crate::frustum_path::{
+    AttachedIterator, Channel, FrustumPath, FrustumPathIterator, Interpolation,
+    KeyHandles, KeyTangents, OrientationMode, ParameterInterpolation,
+    PathMetric, PathParameter, Timing,
+};
+pub use crate::shake::{Shake, ShakeAxis, ShakeIterator};
+pub use crate::spline::SplineKind;
+
+mod fly;
+mod follow;
+mod orbit;
+mod trackball;
+
+pub use crate::fly::FlyController;
+pub use crate::follow::FollowController;
+pub use crate::orbit::OrbitController;
+pub use crate::trackball::TrackballController;
+
+#[cfg(feature = "bytemuck")]
+mod uniform;
+
+#[cfg(feature = "bytemuck")]
+pub use crate::uniform::CameraUniform;
@@ -0,0 +1,134 @@
+use crate::frustum::Frustum;
+
+/// Orbit controller which rotates a frustum around its target.
+///
+/// All inputs are abstract deltas: angles are given in degrees and
+/// distances in world units, independent of any windowing system.
+#[derive(Debug, Copy, Clone)]
+pub struct OrbitController {
+    /// Lower pitch limit in degrees.
+    pub min_pitch: f64,
+    /// Upper pitch limit in degrees.
+    pub max_pitch: f64,
+    /// Minimal distance between origin and target.
+    pub min_distance: f64,
+    /// Maximal distance between origin and target.
+    pub max_distance: f64,
+    /// Lower limit for `fovy` in degrees.
+    pub min_fovy: f64,
+    /// Upper limit for `fovy` in degrees.
+    pub max_fovy: f64,
+    frustum: Frustum,
+    yaw: f64,
+    pitch: f64,
+    distance: f64,
+}
+
+impl OrbitController {
+    pub fn new(frustum: Frustum) -> Self {
+        let mut controller = OrbitController {
+            min_pitch: -89.0,
+            max_pitch: 89.0,
+            min_distance: frustum.ncp,
+            max_distance: f64::INFINITY,
+            min_fovy: 1.0,
+            max_fovy: 179.0,
+            frustum,
+            yaw: 0.0,
+            pitch: 0.0,
+            distance: 0.0,
+        };
+        controller.set_frustum(frustum);
+        controller
+    }
+
+    pub fn frustum(&self) -> &Frustum {
+        &self.frustum
+    }
+
+    /// Replaces the controlled frustum and derives yaw, pitch and
+    /// distance from it. Pitch and distance are clamped to their limits,
+    /// moving the origin if necessary. If origin and target coincide, the
+    /// previous yaw and pitch are kept.
+    pub fn set_frustum(&mut self, frustum: Frustum) {
+        let offset = frustum.origin - frustum.target;
+        let distance = offset.length();
+        let (x, y, z) = frustum.up_frame();
+
+        self.frustum = frustum;
+        if distance > 0.0 {
+            self.pitch = (offset.dot(y) / distance)
+                .clamp(-1.0, 1.0)
+                .asin()
+                .to_degrees();
+            self.yaw = offset.dot(x).atan2(offset.dot(z)).to_degrees();
+        }
+
+        let pitch = self.pitch.max(self.min_pitch).min(self.max_pitch);
+        self.distance = distance.max(self.min_distance).min(self.max_distance);
+        if pitch != self.pitch || self.distance != distance {
+            self.pitch = pitch;
+            self.update_origin();
+        }
+    }
+
+    /// Rotation around the up direction of the frustum in degrees, zero
+    /// towards world z, or world x if the up direction is along z.
+    pub fn yaw(&self) -> f64 {
+        self.yaw
+    }
+
+    /// Elevation above the plane through the target in degrees.
+    pub fn pitch(&self) -> f64 {
+        self.pitch
+    }
+
+    /// Distance between origin and target.
+    pub fn distance(&self) -> f64 {
+        self.distance
+    }
+
+    /// Rotates the camera around the target by the given angles in degrees.
+    pub fn orbit(&mut self, delta_yaw: f64, delta_pitch: f64) {
+        self.yaw = (self.yaw + delta_yaw) % 360.0;
+        self.pitch = (self.pitch + delta_pitch)
+            .max(self.min_pitch)
+            .min(self.max_pitch);
+        self.update_origin();
+    }
+
+    /// Moves the camera towards the target by `delta` world units.
+    pub fn dolly(&mut self, delta: f64) {
+        self.distance = (self.distance - delta)
+            .max(self.min_distance)
+            .min(self.max_distance);
+        self.update_origin();
+    }
+
+    /// Moves origin and target in the view plane by the given amount of
+    /// world units along the right and up direction of the camera.
+    pub fn pan(&mut self, right: f64, up: f64) {
+        let (s, u, _) = self.frustum.basis();
+        let translation = s * right + u * up;
+
+        self.frustum.origin += translation;
+        self.frustum.target += translation;
+    }
+
+    /// Zooms in by narrowing `fovy` by `delta` degrees.
+    pub fn zoom(&mut self, delta: f64) {
+        self.frustum.fovy = (self.frustum.fovy - delta)
+            .max(self.min_fovy)
+            .min(self.max_fovy);
+    }
+
+    fn update_origin(&mut self) {
+        let (yaw, pitch) = (self.yaw.to_radians(), self.pitch.to_radians());
+        let (x, y, z) = self.frustum.up_frame();
+        let direction = x * (pitch.cos() * yaw.sin())
+            + y * pitch.sin()
+            + z * (pitch.cos() * yaw.cos());
+
+        self.frustum.origin = self.frustum.target + direction * self.distance;
+    }
+}
@@ -0,0 +1,249 @@
+use crate::frustum::Frustum;
+use crate::types::*;
+use euclid::Angle;
+
+/// Amplitude and frequency of the noise on one axis.
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct ShakeAxis {
+    /// Largest deviation, in world units or degrees.
+    pub amplitude: f64,
+    /// Frequency of the first octave in Hz.
+    pub frequency: f64,
+}
+
+/// Camera shake from smooth, seeded fractal noise.
+///
+/// The noise is a function of the seed and the time alone, so the same
+/// frame is shaken the same way on every machine and in every order.
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct Shake {
+    pub seed: u64,
+    /// Offset of the camera along its right, up and forward direction.
+    pub translation: [ShakeAxis; 3],
+    /// Yaw, pitch and roll of the camera.
+    pub rotation: [ShakeAxis; 3],
+    /// Octaves of the noise, each with twice the frequency and half the
+    /// amplitude of the previous one.
+    pub octaves: usize,
+}
+
+impl Shake {
+    /// Slow sway and breathing of a camera held by hand. The amplitudes of
+    /// the presets assume a scene in meters.
+    pub fn handheld(seed: u64) -> Self {
+        Shake {
+            seed,
+            translation: axes([(0.01, 0.8), (0.01, 1.0), (0.005, 0.6)]),
+            rotation: axes([(0.6, 0.5), (0.5, 0.6), (0.3, 0.4)]),
+            octaves: 3,
+        }
+    }
+
+    /// Vibration of a camera mounted on a driving car.
+    pub fn vehicle(seed: u64) -> Self {
+        Shake {
+            seed,
+            translation: axes([(0.02, 3.0), (0.04, 5.0), (0.01, 2.0)]),
+            rotation: axes([(0.2, 2.0), (0.4, 4.0), (0.3, 1.5)]),
+            octaves: 4,
+        }
+    }
+
+    /// Violent shaking in all directions.
+    pub fn earthquake(seed: u64) -> Self {
+        Shake {
+            seed,
+            translation: axes([(0.1, 6.0), (0.15, 8.0), (0.1, 6.0)]),
+            rotation: axes([(1.5, 4.0), (1.5, 5.0), (2.0, 4.0)]),
+            octaves: 4,
+        }
+    }
+
+    /// Shakes a frustum at the given time in seconds. The camera is moved
+    /// and turned about its origin, the distance to the target is kept.
+    pub fn apply(&self, frustum: &Frustum, time: f64) -> Frustum {
+        let noise = |channel: u64, axis: &ShakeAxis| {
+            axis.amplitude
+                * fractal_noise(
+                    self.seed,
+                    channel,
+                    time * axis.frequency,
+                    self.octaves,
+                )
+        };
+        let (s, u, f) = frustum.basis();
+
+        let offset = s * noise(0, &self.translation[0])
+            + u * noise(1, &self.translation[1])
+            + f * noise(2, &self.translation[2]);
+
+        let rotations = [
+            (u, noise(3, &self.rotation[0])),
+            (s, noise(4, &self.rotation[1])),
+            (f, noise(5, &self.rotation[2])),
+        ];
+        let (mut view, mut up) = (frustum.target - frustum.origin, frustum.up);
+        for (axis, angle) in rotations.iter() {
+            let rotation: Rotation3<WorldSpace, WorldSpace> =
+                Rotation3::around_axis(*axis, Angle::degrees(*angle));
+            view = rotation.transform_vector3d(view);
+            up = rotation.transform_vector3d(up);
+        }
+
+        Frustum {
+            origin: frustum.origin + offset,
+            target: frustum.origin + offset + view,
+            up,
+            ..*frustum
+        }
+    }
+
+    /// Shakes a sequence of frames, such as a `FrustumPathIterator`, which
+    /// are `1 / frames_per_second` apart.
+    pub fn iter<I>(
+        &self,
+        frames: I,
+        frames_per_second: f64,
+    ) -> ShakeIterator<I::IntoIter>
+    where
+        I: IntoIterator<Item = Frustum>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        let frames = frames.into_iter();
+
+        ShakeIterator {
+            shake: *self,
+            frames_per_second,
+            front: 0,
+            back: frames.len(),
+            frames,
+        }
+    }
+}
+
+/// Builds the axes of a preset from amplitudes and frequencies.
+fn axes(values: [(f64, f64); 3]) -> [ShakeAxis; 3] {
+    let axis = |(amplitude, frequency): (f64, f64)| ShakeAxis {
+        amplitude,
+        frequency,
+    };
+
+    [axis(values[0]), axis(values[1]), axis(values[2])]
+}
+
+/// Fractal gradient noise in `[-1, 1]`. Each channel and octave has its
+/// own lattice and phase, so they are independent of each other.
+fn fractal_noise(seed: u64, channel: u64, x: f64, octaves: usize) -> f64 {
+    let (mut sum, mut total) = (0., 0.);
+    let (mut amplitude, mut frequency) = (1., 1.);
+
+    for octave in 0..octaves.max(1) as u64 {
+        let lattice = hash(seed ^ hash(channel << 8 | octave));
+        // A random phase keeps the lattice points, where the noise is
+        // zero, apart across channels.
+        let phase = 1024. * unit(hash(lattice));
+
+        sum += amplitude * gradient_noise(lattice, x * frequency + phase);
+        total += amplitude;
+        amplitude *= 0.5;
+        frequency *= 2.;
+    }
+
+    sum / total
+}
+
+/// One-dimensional Perlin noise in `[-1, 1]`, with random gradients at
+/// the integers.
+fn gradient_noise(lattice: u64, x: f64) -> f64 {
+    let cell = x.floor();
+    let t = x - cell;
+    let gradient = |cell: i64| 2. * unit(hash(lattice ^ cell as u64)) - 1.;
+    let (g0, g1) = (gradient(cell as i64), gradient(cell as i64 + 1));
+
+    let fade = t * t * t * (t * (t * 6. - 15.) + 10.);
+    let (n0, n1) = (g0 * t, g1 * (t - 1.));
+
+    // The noise reaches at most half the gradient between two integers.
+    2. * (n0 + fade * (n1 - n0))
+}
+
+/// SplitMix64 finalizer, a fast hash with good avalanche.
+fn hash(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// Maps a hash to `[0, 1)`.
+fn unit(hash: u64) -> f64 {
+    (hash >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Iterator which shakes the frames of another iterator.
+pub struct ShakeIterator<I> {
+    shake: Shake,
+    frames_per_second: f64,
+    frames: I,
+    front: usize,
+    back: usize,
+}
+
+impl<I: Iterator<Item = Frustum>> Iterator for ShakeIterator<I> {
+    type Item = Frustum;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let frame = self.frames.next()?;
+        let time = self.front as f64 / self.frames_per_second;
+        self.front += 1;
+
+        Some(self.shake.apply(&frame, time))
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        let frame = self.frames.nth(n)?;
+        self.front += n;
+        let time = self.front as f64 / self.frames_per_second;
+        self.front += 1;
+
+        Some(self.shake.apply(&frame, time))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.frames.size_hint()
+    }
+}
+
+impl<I> DoubleEndedIterator for ShakeIterator<I>
+where
+    I: DoubleEndedIterator<Item = Frustum>,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let frame = self.frames.next_back()?;
+        self.back -= 1;
+        let time = self.back as f64 / self.frames_per_second;
+
+        Some(self.shake.apply(&frame, time))
+    }
+
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        let frame = self.frames.nth_back(n)?;
+        self.back -= n + 1;
+        let time = self.back as f64 / self.frames_per_second;
+
+        Some(self.shake.apply(&frame, time))
+    }
+}
+
+impl<I: ExactSizeIterator<Item = Frustum>> ExactSizeIterator
+    for ShakeIterator<I>
+{
+}
@@ -1,8 +1,66 @@
+use crate::arc_length::ArcLength;
 use crate::types::*;
+use euclid::Vector3D;
+use std::ops::{Add, Mul, Sub};
 
 pub trait Spline<T> {
+    /// Type of the derivatives, like a vector for points.
+    type Tangent;
+
     fn catmull_rom(v1: &T, v2: &T, v3: &T, v4: &T, s: f64) -> T;
-    fn length_for_segment(values: &Vec<T>, segment_idx: usize) -> f64;
+    /// First derivative of `catmull_rom` with respect to `s`.
+    fn catmull_rom_derivative(
+        v1: &T,
+        v2: &T,
+        v3: &T,
+        v4: &T,
+        s: f64,
+    ) -> Self::Tangent;
+    /// Second derivative of `catmull_rom` with respect to `s`.
+    fn catmull_rom_second_derivative(
+        v1: &T,
+        v2: &T,
+        v3: &T,
+        v4: &T,
+        s: f64,
+    ) -> Self::Tangent;
+    /// Arc-length table of a segment, which gives its length and maps
+    /// distances on it back to spline positions.
+    fn length_for_segment(
+        kind: &SplineKind,
+        values: &[T],
+        segment_idx: usize,
+    ) -> ArcLength;
+
+    /// Evaluates a segment with the given spline family. Values which do not
+    /// support the family fall back to `catmull_rom`.
+    fn spline(_kind: &SplineKind, v1: &T, v2: &T, v3: &T, v4: &T, s: f64) -> T {
+        Self::catmull_rom(v1, v2, v3, v4, s)
+    }
+
+    /// First derivative of `spline` with respect to `s`.
+    fn spline_derivative(
+        _kind: &SplineKind,
+        v1: &T,
+        v2: &T,
+        v3: &T,
+        v4: &T,
+        s: f64,
+    ) -> Self::Tangent {
+        Self::catmull_rom_derivative(v1, v2, v3, v4, s)
+    }
+
+    /// Second derivative of `spline` with respect to `s`.
+    fn spline_second_derivative(
+        _kind: &SplineKind,
+        v1: &T,
+        v2: &T,
+        v3: &T,
+        v4: &T,
+        s: f64,
+    ) -> Self::Tangent {
+        Self::catmull_rom_second_derivative(v1, v2, v3, v4, s)
+    }
 
     fn get_control_points_for_segment(
         segment_idx: usize,
@@ -17,54 +75,422 @@ pub trait Spline<T> {
         )
     }
 
-    fn length(values: &Vec<T>) -> (f64, Vec<f64>) {
-        let segment_lengths = (0..values.len() - 1)
-            .map(|i| Self::length_for_segment(values, i))
-            .collect::<Vec<_>>();
+    /// Control points of a segment on a closed path, where the segment
+    /// after the last value leads back to the first one.
+    fn get_control_points_for_closed_segment(
+        segment_idx: usize,
+        path_length: usize,
+    ) -> (usize, usize, usize, usize) {
+        (
+            (segment_idx + path_length - 1) % path_length,
+            segment_idx % path_length,
+            (segment_idx + 1) % path_length,
+            (segment_idx + 2) % path_length,
+        )
+    }
+}
+
+/// Spline family used to interpolate between key frustums.
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub enum SplineKind {
+    /// Uniform Catmull-Rom spline.
+    #[default]
+    CatmullRom,
+    /// Centripetal Catmull-Rom spline. It neither overshoots nor forms
+    /// cusps on unevenly spaced keys.
+    Centripetal,
+    /// Chordal Catmull-Rom spline.
+    Chordal,
+    /// Cubic Bezier spline through the keys, shaped by the explicit
+    /// `key_handles` of the path. Only origin and target have handles, the
+    /// other parameters follow the uniform Catmull-Rom spline.
+    Bezier,
+    /// Uniform cubic B-spline. It is C2 continuous, but only approximates
+    /// the keys.
+    BSpline,
+    /// Cubic Hermite spline using the explicit `key_tangents` of the path.
+    /// Only origin and target have tangents, the other parameters follow
+    /// the uniform Catmull-Rom spline.
+    Hermite,
+    /// Kochanek-Bartels spline. All parameters at zero give the uniform
+    /// Catmull-Rom spline. A continuity of -1 gives straight segments
+    /// with sharp corners at the keys.
+    KochanekBartels {
+        tension: f64,
+        continuity: f64,
+        bias: f64,
+    },
+}
 
-        (segment_lengths.iter().sum(), segment_lengths)
+/// Values which can be interpolated by a spline.
+pub trait SplineValue:
+    Copy + Add<Output = Self> + Sub<Output = Self> + Mul<f64, Output = Self>
+{
+    fn norm(&self) -> f64;
+}
+
+impl SplineValue for f64 {
+    fn norm(&self) -> f64 {
+        self.abs()
+    }
+}
+
+impl<U> SplineValue for Vector3D<f64, U> {
+    fn norm(&self) -> f64 {
+        self.length()
+    }
+}
+
+impl SplineKind {
+    /// The spline for values without explicit handles or tangents, which
+    /// is the uniform Catmull-Rom spline for Bezier and Hermite.
+    pub fn scalar(&self) -> SplineKind {
+        match self {
+            SplineKind::Bezier | SplineKind::Hermite => SplineKind::CatmullRom,
+            kind => *kind,
+        }
+    }
+
+    /// Evaluates the segment between `v2` and `v3` with `v1` and `v4` as
+    /// neighbours.
+    ///
+    /// Panics for Bezier and Hermite splines, which need explicit handles
+    /// or tangents. Use `scalar` for values without them.
+    pub fn evaluate<V: SplineValue>(
+        &self,
+        v1: V,
+        v2: V,
+        v3: V,
+        v4: V,
+        s: f64,
+    ) -> V {
+        self.evaluate_order(v1, v2, v3, v4, s, 0)
     }
+
+    /// First derivative of `evaluate` with respect to `s`.
+    pub fn derivative<V: SplineValue>(
+        &self,
+        v1: V,
+        v2: V,
+        v3: V,
+        v4: V,
+        s: f64,
+    ) -> V {
+        self.evaluate_order(v1, v2, v3, v4, s, 1)
+    }
+
+    /// Second derivative of `evaluate` with respect to `s`.
+    pub fn second_derivative<V: SplineValue>(
+        &self,
+        v1: V,
+        v2: V,
+        v3: V,
+        v4: V,
+        s: f64,
+    ) -> V {
+        self.evaluate_order(v1, v2, v3, v4, s, 2)
+    }
+
+    /// Evaluates the derivative of the given order, where order 0 is the
+    /// spline itself.
+    fn evaluate_order<V: SplineValue>(
+        &self,
+        v1: V,
+        v2: V,
+        v3: V,
+        v4: V,
+        s: f64,
+        order: usize,
+    ) -> V {
+        match *self {
+            SplineKind::Centripetal => {
+                non_uniform(v1, v2, v3, v4, 0.5, s, order)
+            }
+            SplineKind::Chordal => non_uniform(v1, v2, v3, v4, 1., s, order),
+            SplineKind::BSpline => {
+                weighted([v1, v2, v3, v4], bspline_basis(s, order)) * (1. / 6.)
+            }
+            SplineKind::KochanekBartels {
+                tension,
+                continuity,
+                bias,
+            } => {
+                // Outgoing tangent at v2 and incoming tangent at v3.
+                let t = 1. - tension;
+                let m2 = (v2 - v1) * (t * (1. + bias) * (1. + continuity) / 2.)
+                    + (v3 - v2) * (t * (1. - bias) * (1. - continuity) / 2.);
+                let m3 = (v3 - v2) * (t * (1. + bias) * (1. - continuity) / 2.)
+                    + (v4 - v3) * (t * (1. - bias) * (1. + continuity) / 2.);
+
+                hermite_derivative(v2, m2, v3, m3, s, order)
+            }
+            SplineKind::CatmullRom => {
+                weighted([v1, v2, v3, v4], catmull_rom_basis(s, order)) * 0.5
+            }
+            SplineKind::Bezier | SplineKind::Hermite => panic!(
+                "{:?} splines need explicit handles or tangents, see \
+                 SplineKind::scalar.",
+                self
+            ),
+        }
+    }
+}
+
+/// Sum of the values weighted by the weights.
+fn weighted<V: SplineValue>(values: [V; 4], weights: [f64; 4]) -> V {
+    values[0] * weights[0]
+        + values[1] * weights[1]
+        + values[2] * weights[2]
+        + values[3] * weights[3]
+}
+
+/// Uniform Catmull-Rom basis, or its derivative of the given order, scaled
+/// by two.
+pub fn catmull_rom_basis(s: f64, order: usize) -> [f64; 4] {
+    let s2 = s * s;
+    let s3 = s2 * s;
+
+    match order {
+        0 => [
+            -s3 + 2. * s2 - s,
+            3. * s3 - 5. * s2 + 2.,
+            -3. * s3 + 4. * s2 + s,
+            s3 - s2,
+        ],
+        1 => [
+            -3. * s2 + 4. * s - 1.,
+            9. * s2 - 10. * s,
+            -9. * s2 + 8. * s + 1.,
+            3. * s2 - 2. * s,
+        ],
+        _ => [-6. * s + 4., 18. * s - 10., -18. * s + 8., 6. * s - 2.],
+    }
+}
+
+/// Uniform cubic B-spline basis, or its derivative of the given order,
+/// scaled by six.
+fn bspline_basis(s: f64, order: usize) -> [f64; 4] {
+    let s2 = s * s;
+    let s3 = s2 * s;
+    let t = 1. - s;
+
+    match order {
+        0 => [
+            t * t * t,
+            3. * s3 - 6. * s2 + 4.,
+            -3. * s3 + 3. * s2 + 3. * s + 1.,
+            s3,
+        ],
+        1 => [
+            -3. * t * t,
+            9. * s2 - 12. * s,
+            -9. * s2 + 6. * s + 3.,
+            3. * s2,
+        ],
+        _ => [6. * t, 18. * s - 12., -18. * s + 6., 6. * s],
+    }
+}
+
+/// Cubic Hermite basis for `(v1, m1, v2, m2)`, or its derivative of the
+/// given order.
+pub fn hermite_basis(s: f64, order: usize) -> [f64; 4] {
+    let s2 = s * s;
+    let s3 = s2 * s;
+
+    match order {
+        0 => [
+            2. * s3 - 3. * s2 + 1.,
+            s3 - 2. * s2 + s,
+            -2. * s3 + 3. * s2,
+            s3 - s2,
+        ],
+        1 => [
+            6. * s2 - 6. * s,
+            3. * s2 - 4. * s + 1.,
+            -6. * s2 + 6. * s,
+            3. * s2 - 2. * s,
+        ],
+        _ => [12. * s - 6., 6. * s - 4., -12. * s + 6., 6. * s - 2.],
+    }
+}
+
+/// Cubic Bernstein basis, or its derivative of the given order.
+fn bezier_basis(s: f64, order: usize) -> [f64; 4] {
+    let t = 1. - s;
+
+    match order {
+        0 => [t * t * t, 3. * t * t * s, 3. * t * s * s, s * s * s],
+        1 => [
+            -3. * t * t,
+            3. * t * t - 6. * t * s,
+            6. * t * s - 3. * s * s,
+            3. * s * s,
+        ],
+        _ => [6. * t, 6. * s - 12. * t, 6. * t - 12. * s, 6. * s],
+    }
+}
+
+/// Cubic Hermite spline from `v1` with tangent `m1` to `v2` with tangent
+/// `m2`.
+pub fn hermite<V: SplineValue>(v1: V, m1: V, v2: V, m2: V, s: f64) -> V {
+    hermite_derivative(v1, m1, v2, m2, s, 0)
+}
+
+/// Derivative of the given order of `hermite` with respect to `s`.
+pub fn hermite_derivative<V: SplineValue>(
+    v1: V,
+    m1: V,
+    v2: V,
+    m2: V,
+    s: f64,
+    order: usize,
+) -> V {
+    weighted([v1, m1, v2, m2], hermite_basis(s, order))
+}
+
+/// Cubic Bezier curve from `v1` to `v2` with the control points `c1` and
+/// `c2`, or its derivative of the given order with respect to `s`.
+pub fn bezier_derivative<V: SplineValue>(
+    v1: V,
+    c1: V,
+    c2: V,
+    v2: V,
+    s: f64,
+    order: usize,
+) -> V {
+    weighted([v1, c1, c2, v2], bezier_basis(s, order))
+}
+
+/// Catmull-Rom spline with knots spaced by the distance of the control
+/// points to the power of `alpha`, written in Hermite form.
+fn non_uniform<V: SplineValue>(
+    v1: V,
+    v2: V,
+    v3: V,
+    v4: V,
+    alpha: f64,
+    s: f64,
+    order: usize,
+) -> V {
+    let mut d12 = (v3 - v2).norm().powf(alpha);
+    if d12 < f64::EPSILON {
+        d12 = 1.;
+    }
+
+    // Clamped end points coincide with their neighbour, mirror the
+    // spacing of the segment instead.
+    let mut d01 = (v2 - v1).norm().powf(alpha);
+    if d01 < f64::EPSILON {
+        d01 = d12;
+    }
+    let mut d23 = (v4 - v3).norm().powf(alpha);
+    if d23 < f64::EPSILON {
+        d23 = d12;
+    }
+
+    let m2 = ((v2 - v1) * (1. / d01) - (v3 - v1) * (1. / (d01 + d12))
+        + (v3 - v2) * (1. / d12))
+        * d12;
+    let m3 = ((v3 - v2) * (1. / d12) - (v4 - v2) * (1. / (d12 + d23))
+        + (v4 - v3) * (1. / d23))
+        * d12;
+
+    hermite_derivative(v2, m2, v3, m3, s, order)
 }
 
 pub struct Spline1;
 
 impl Spline<f64> for Spline1 {
+    type Tangent = f64;
+
     fn catmull_rom(v1: &f64, v2: &f64, v3: &f64, v4: &f64, s: f64) -> f64 {
-        let s2 = s * s;
-        let s3 = s2 * s;
-        let f1 = -s3 + 2. * s2 - s;
-        let f2 = 3. * s3 - 5. * s2 + 2.;
-        let f3 = -3. * s3 + 4. * s2 + s;
-        let f4 = s3 - s2;
+        SplineKind::CatmullRom.evaluate(*v1, *v2, *v3, *v4, s)
+    }
 
-        (v1 * f1 + v2 * f2 + v3 * f3 + v4 * f4) * 0.5
+    fn catmull_rom_derivative(
+        v1: &f64,
+        v2: &f64,
+        v3: &f64,
+        v4: &f64,
+        s: f64,
+    ) -> f64 {
+        SplineKind::CatmullRom.derivative(*v1, *v2, *v3, *v4, s)
     }
 
-    fn length_for_segment(values: &Vec<f64>, segment_idx: usize) -> f64 {
+    fn catmull_rom_second_derivative(
+        v1: &f64,
+        v2: &f64,
+        v3: &f64,
+        v4: &f64,
+        s: f64,
+    ) -> f64 {
+        SplineKind::CatmullRom.second_derivative(*v1, *v2, *v3, *v4, s)
+    }
+
+    fn spline(
+        kind: &SplineKind,
+        v1: &f64,
+        v2: &f64,
+        v3: &f64,
+        v4: &f64,
+        s: f64,
+    ) -> f64 {
+        kind.evaluate(*v1, *v2, *v3, *v4, s)
+    }
+
+    fn spline_derivative(
+        kind: &SplineKind,
+        v1: &f64,
+        v2: &f64,
+        v3: &f64,
+        v4: &f64,
+        s: f64,
+    ) -> f64 {
+        kind.derivative(*v1, *v2, *v3, *v4, s)
+    }
+
+    fn spline_second_derivative(
+        kind: &SplineKind,
+        v1: &f64,
+        v2: &f64,
+        v3: &f64,
+        v4: &f64,
+        s: f64,
+    ) -> f64 {
+        kind.second_derivative(*v1, *v2, *v3, *v4, s)
+    }
+
+    fn length_for_segment(
+        kind: &SplineKind,
+        values: &[f64],
+        segment_idx: usize,
+    ) -> ArcLength {
         let (idx0, idx1, idx2, idx3) =
             Self::get_control_points_for_segment(segment_idx, values.len());
 
         let (cp0, cp1, cp2, cp3) =
             (&values[idx0], &values[idx1], &values[idx2], &values[idx3]);
 
-        let mut last = *cp1;
-        if cp1 - cp2 < std::f64::EPSILON {
-            return 0.;
-        }
-        let mut length = 0.0;
-        for i in 0..1000 {
-            let t = i as f64 * 0.001;
-            let current = Self::catmull_rom(cp0, cp1, cp2, cp3, t);
-            length += current - last;
-            last = current;
+        if (cp1 - cp2).abs() < f64::EPSILON {
+            return ArcLength::zero();
         }
-        length
+
+        ArcLength::new(
+            |s| Self::spline(kind, cp0, cp1, cp2, cp3, s),
+            |a, b| (a - b).abs(),
+        )
     }
 }
 
 pub struct Spline3;
 
 impl Spline<Point3<WorldSpace>> for Spline3 {
+    type Tangent = Vec3<WorldSpace>;
+
     fn catmull_rom(
         v1: &Point3<WorldSpace>,
         v2: &Point3<WorldSpace>,
@@ -72,42 +498,381 @@ impl Spline<Point3<WorldSpace>> for Spline3 {
         v4: &Point3<WorldSpace>,
         s: f64,
     ) -> Point3<WorldSpace> {
-        let s2 = s * s;
-        let s3 = s2 * s;
-        let f1 = -s3 + 2. * s2 - s;
-        let f2 = 3. * s3 - 5. * s2 + 2.;
-        let f3 = -3. * s3 + 4. * s2 + s;
-        let f4 = s3 - s2;
+        Self::spline(&SplineKind::CatmullRom, v1, v2, v3, v4, s)
+    }
+
+    fn catmull_rom_derivative(
+        v1: &Point3<WorldSpace>,
+        v2: &Point3<WorldSpace>,
+        v3: &Point3<WorldSpace>,
+        v4: &Point3<WorldSpace>,
+        s: f64,
+    ) -> Vec3<WorldSpace> {
+        Self::spline_derivative(&SplineKind::CatmullRom, v1, v2, v3, v4, s)
+    }
+
+    fn catmull_rom_second_derivative(
+        v1: &Point3<WorldSpace>,
+        v2: &Point3<WorldSpace>,
+        v3: &Point3<WorldSpace>,
+        v4: &Point3<WorldSpace>,
+        s: f64,
+    ) -> Vec3<WorldSpace> {
+        Self::spline_second_derivative(
+            &SplineKind::CatmullRom,
+            v1,
+            v2,
+            v3,
+            v4,
+            s,
+        )
+    }
+
+    fn spline_derivative(
+        kind: &SplineKind,
+        v1: &Point3<WorldSpace>,
+        v2: &Point3<WorldSpace>,
+        v3: &Point3<WorldSpace>,
+        v4: &Point3<WorldSpace>,
+        s: f64,
+    ) -> Vec3<WorldSpace> {
+        kind.derivative(
+            v1.to_vector(),
+            v2.to_vector(),
+            v3.to_vector(),
+            v4.to_vector(),
+            s,
+        )
+    }
+
+    fn spline_second_derivative(
+        kind: &SplineKind,
+        v1: &Point3<WorldSpace>,
+        v2: &Point3<WorldSpace>,
+        v3: &Point3<WorldSpace>,
+        v4: &Point3<WorldSpace>,
+        s: f64,
+    ) -> Vec3<WorldSpace> {
+        kind.second_derivative(
+            v1.to_vector(),
+            v2.to_vector(),
+            v3.to_vector(),
+            v4.to_vector(),
+            s,
+        )
+    }
+
+    fn spline(
+        kind: &SplineKind,
+        v1: &Point3<WorldSpace>,
+        v2: &Point3<WorldSpace>,
+        v3: &Point3<WorldSpace>,
+        v4: &Point3<WorldSpace>,
+        s: f64,
+    ) -> Point3<WorldSpace> {
+        kind.evaluate(
+            v1.to_vector(),
+            v2.to_vector(),
+            v3.to_vector(),
+            v4.to_vector(),
+            s,
+        )
+        .to_point()
+    }
+
+    fn length_for_segment(
+        kind: &SplineKind,
+        values: &[Point3<WorldSpace>],
+        segment_idx: usize,
+    ) -> ArcLength {
+        let (idx0, idx1, idx2, idx3) =
+            Self::get_control_points_for_segment(segment_idx, values.len());
+
+        let (cp0, cp1, cp2, cp3) =
+            (&values[idx0], &values[idx1], &values[idx2], &values[idx3]);
 
-        (*v1 * f1
-            + v2.to_vector() * f2
-            + v3.to_vector() * f3
-            + v4.to_vector() * f4)
-            * 0.5
+        if (*cp1 - *cp2).length() < f64::EPSILON {
+            return ArcLength::zero();
+        }
+
+        ArcLength::new(
+            |s| Self::spline(kind, cp0, cp1, cp2, cp3, s),
+            |a, b| (*a - *b).length(),
+        )
     }
+}
+
+pub type Orientation = Rotation3<CameraSpace, WorldSpace>;
+
+/// Step of the central differences for the derivatives of orientations.
+const DERIVATIVE_STEP: f64 = 1e-4;
+
+/// Constant angular velocity in world space which turns `from` into `to`
+/// within `dt`.
+pub fn angular_velocity(
+    from: &Orientation,
+    to: &Orientation,
+    dt: f64,
+) -> Vec3<WorldSpace> {
+    let q1 = Quaternion::from(from);
+    let delta = Quaternion::from(to).closest_to(&q1).mul(&q1.conjugate());
+
+    let axis = Vec3::new(delta.i, delta.j, delta.k);
+    let sin = axis.length();
+    if sin < f64::EPSILON {
+        return Vec3::zero();
+    }
+
+    axis * (2. * sin.atan2(delta.r) / sin / dt)
+}
+
+/// Spherical spline for orientations (squad), the quaternion counterpart
+/// to the Catmull-Rom spline.
+pub struct SplineQ;
+
+impl SplineQ {
+    /// Spherical linear interpolation along the shortest arc.
+    pub fn slerp(v1: &Orientation, v2: &Orientation, s: f64) -> Orientation {
+        Quaternion::from(v1).slerp(&Quaternion::from(v2), s).into()
+    }
+}
 
+impl Spline<Orientation> for SplineQ {
+    /// Angular velocity in world space, in radians per unit of `s`.
+    type Tangent = Vec3<WorldSpace>;
+
+    fn catmull_rom(
+        v1: &Orientation,
+        v2: &Orientation,
+        v3: &Orientation,
+        v4: &Orientation,
+        s: f64,
+    ) -> Orientation {
+        // Keep all quaternions in the hemisphere of their neighbour, so the
+        // spline takes the short way.
+        let q1 = Quaternion::from(v2);
+        let q0 = Quaternion::from(v1).closest_to(&q1);
+        let q2 = Quaternion::from(v3).closest_to(&q1);
+        let q3 = Quaternion::from(v4).closest_to(&q2);
+
+        let a1 = q1.inner_control_point(&q0, &q2);
+        let a2 = q2.inner_control_point(&q1, &q3);
+
+        // The signs are fixed above, flipping them again in the slerps
+        // would break the continuity between segments.
+        let q = q1.slerp_unflipped(&q2, s);
+        let a = a1.slerp_unflipped(&a2, s);
+
+        q.slerp_unflipped(&a, 2. * s * (1. - s)).into()
+    }
+
+    /// The angular velocity is calculated numerically.
+    fn catmull_rom_derivative(
+        v1: &Orientation,
+        v2: &Orientation,
+        v3: &Orientation,
+        v4: &Orientation,
+        s: f64,
+    ) -> Vec3<WorldSpace> {
+        let h = DERIVATIVE_STEP;
+
+        angular_velocity(
+            &Self::catmull_rom(v1, v2, v3, v4, s - h),
+            &Self::catmull_rom(v1, v2, v3, v4, s + h),
+            2. * h,
+        )
+    }
+
+    fn catmull_rom_second_derivative(
+        v1: &Orientation,
+        v2: &Orientation,
+        v3: &Orientation,
+        v4: &Orientation,
+        s: f64,
+    ) -> Vec3<WorldSpace> {
+        let h = DERIVATIVE_STEP;
+        let w1 = Self::catmull_rom_derivative(v1, v2, v3, v4, s - h);
+        let w2 = Self::catmull_rom_derivative(v1, v2, v3, v4, s + h);
+
+        (w2 - w1) / (2. * h)
+    }
+
+    /// Measures the angle in radians the segment rotates by.
     fn length_for_segment(
-        values: &Vec<Point3<WorldSpace>>,
+        _kind: &SplineKind,
+        values: &[Orientation],
         segment_idx: usize,
-    ) -> f64 {
+    ) -> ArcLength {
         let (idx0, idx1, idx2, idx3) =
             Self::get_control_points_for_segment(segment_idx, values.len());
 
         let (cp0, cp1, cp2, cp3) =
             (&values[idx0], &values[idx1], &values[idx2], &values[idx3]);
 
-        let mut last = *cp1;
-        if (*cp1 - *cp2).length() < std::f64::EPSILON {
-            return 0.;
+        ArcLength::new(
+            |s| Quaternion::from(&Self::catmull_rom(cp0, cp1, cp2, cp3, s)),
+            |a, b| a.angle_to(b),
+        )
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+struct Quaternion {
+    i: f64,
+    j: f64,
+    k: f64,
+    r: f64,
+}
+
+impl Quaternion {
+    fn dot(&self, other: &Self) -> f64 {
+        self.i * other.i
+            + self.j * other.j
+            + self.k * other.k
+            + self.r * other.r
+    }
+
+    fn scale(&self, s: f64) -> Self {
+        Quaternion {
+            i: self.i * s,
+            j: self.j * s,
+            k: self.k * s,
+            r: self.r * s,
+        }
+    }
+
+    fn add(&self, other: &Self) -> Self {
+        Quaternion {
+            i: self.i + other.i,
+            j: self.j + other.j,
+            k: self.k + other.k,
+            r: self.r + other.r,
         }
-        let mut length = 0.0;
-        for i in 0..1000 {
-            let t = i as f64 * 0.001;
-            let current = Self::catmull_rom(cp0, cp1, cp2, cp3, t);
-            length += (current - last).length();
-            last = current;
+    }
+
+    fn mul(&self, other: &Self) -> Self {
+        Quaternion {
+            i: self.r * other.i + self.i * other.r + self.j * other.k
+                - self.k * other.j,
+            j: self.r * other.j - self.i * other.k
+                + self.j * other.r
+                + self.k * other.i,
+            k: self.r * other.k + self.i * other.j - self.j * other.i
+                + self.k * other.r,
+            r: self.r * other.r
+                - self.i * other.i
+                - self.j * other.j
+                - self.k * other.k,
         }
-        length
+    }
+
+    fn conjugate(&self) -> Self {
+        Quaternion {
+            i: -self.i,
+            j: -self.j,
+            k: -self.k,
+            r: self.r,
+        }
+    }
+
+    /// Angle of the rotation between two unit quaternions. Unlike `acos`
+    /// of the dot product it stays accurate for small angles.
+    fn angle_to(&self, other: &Self) -> f64 {
+        let difference = self.add(&other.closest_to(self).scale(-1.));
+
+        4. * (difference.dot(&difference).sqrt() / 2.).min(1.).asin()
+    }
+
+    fn closest_to(&self, other: &Self) -> Self {
+        if self.dot(other) < 0. {
+            self.scale(-1.)
+        } else {
+            *self
+        }
+    }
+
+    fn log(&self) -> Self {
+        let length =
+            (self.i * self.i + self.j * self.j + self.k * self.k).sqrt();
+        let angle = length.atan2(self.r);
+        let s = if length < f64::EPSILON {
+            0.
+        } else {
+            angle / length
+        };
+
+        Quaternion {
+            i: self.i * s,
+            j: self.j * s,
+            k: self.k * s,
+            r: 0.,
+        }
+    }
+
+    fn exp(&self) -> Self {
+        let angle =
+            (self.i * self.i + self.j * self.j + self.k * self.k).sqrt();
+        let s = if angle < f64::EPSILON {
+            1.
+        } else {
+            angle.sin() / angle
+        };
+
+        Quaternion {
+            i: self.i * s,
+            j: self.j * s,
+            k: self.k * s,
+            r: angle.cos(),
+        }
+    }
+
+    /// Spherical linear interpolation along the shortest arc.
+    fn slerp(&self, other: &Self, t: f64) -> Self {
+        self.slerp_unflipped(&other.closest_to(self), t)
+    }
+
+    /// Spherical linear interpolation between the quaternions as given,
+    /// which takes the long arc if they lie in opposite hemispheres.
+    fn slerp_unflipped(&self, other: &Self, t: f64) -> Self {
+        let dot = self.dot(other).clamp(-1., 1.);
+
+        // Antipodal quaternions are the same rotation.
+        let (a, b) = if dot.abs() > 1. - 1e-9 {
+            (1. - t, t * dot.signum())
+        } else {
+            let angle = dot.acos();
+            let sin = angle.sin();
+            (((1. - t) * angle).sin() / sin, (t * angle).sin() / sin)
+        };
+
+        let q = self.scale(a).add(&other.scale(b));
+        q.scale(1. / q.dot(&q).sqrt())
+    }
+
+    /// Control point of squad between the quaternion and its neighbours.
+    fn inner_control_point(&self, previous: &Self, next: &Self) -> Self {
+        let inverse = self.conjugate();
+        let to_next = inverse.mul(next).log();
+        let to_previous = inverse.mul(previous).log();
+
+        self.mul(&to_next.add(&to_previous).scale(-0.25).exp())
+    }
+}
+
+impl From<&Orientation> for Quaternion {
+    fn from(rotation: &Orientation) -> Self {
+        Quaternion {
+            i: rotation.i,
+            j: rotation.j,
+            k: rotation.k,
+            r: rotation.r,
+        }
+    }
+}
+
+impl From<Quaternion> for Orientation {
+    fn from(q: Quaternion) -> Self {
+        Rotation3::quaternion(q.i, q.j, q.k, q.r)
     }
 }
 
@@ -115,6 +880,8 @@ impl Spline<Point3<WorldSpace>> for Spline3 {
 mod tests {
     use crate::spline::Spline;
     use crate::spline::Spline3;
+    use crate::spline::SplineKind;
+    use crate::spline::{angular_velocity, Orientation, SplineQ};
     use crate::types::*;
     use pretty_assertions::assert_eq;
 
@@ -186,4 +953,179 @@ mod tests {
             Point3::<WorldSpace>::new(1.000000, 2.000000, 1.000000)
         );
     }
+
+    #[test]
+    fn test_spline_kinds() {
+        let (p0, p1, p2, p3) = (0.0, 1.0, 1.1, 5.0);
+        let kinds = [
+            SplineKind::CatmullRom,
+            SplineKind::Centripetal,
+            SplineKind::Chordal,
+            SplineKind::KochanekBartels {
+                tension: 0.5,
+                continuity: -0.5,
+                bias: 0.2,
+            },
+        ];
+
+        for kind in kinds.iter() {
+            assert_eq!(kind.evaluate(p0, p1, p2, p3, 0.), p1);
+            assert!((kind.evaluate(p0, p1, p2, p3, 1.) - p2).abs() < 1e-12);
+        }
+
+        // Uniform Catmull-Rom overshoots the short segment, the
+        // centripetal and chordal variants stay within it.
+        let overshoot = |kind: SplineKind| {
+            (0..=100)
+                .map(|i| kind.evaluate(p0, p1, p2, p3, i as f64 / 100.))
+                .any(|v| v < p1 - 1e-12 || v > p2 + 1e-12)
+        };
+        assert!(overshoot(SplineKind::CatmullRom));
+        assert!(!overshoot(SplineKind::Centripetal));
+        assert!(!overshoot(SplineKind::Chordal));
+
+        let tcb = SplineKind::KochanekBartels {
+            tension: 0.,
+            continuity: 0.,
+            bias: 0.,
+        };
+        assert!(
+            (tcb.evaluate(p0, p1, p2, p3, 0.3)
+                - SplineKind::CatmullRom.evaluate(p0, p1, p2, p3, 0.3))
+            .abs()
+                < 1e-12
+        );
+
+        assert_eq!(
+            SplineKind::BSpline.evaluate(0., 6., 12., 0., 0.),
+            (0. + 4. * 6. + 12.) / 6.
+        );
+
+        assert_eq!(SplineKind::Hermite.scalar(), SplineKind::CatmullRom);
+        assert_eq!(SplineKind::Bezier.scalar(), SplineKind::CatmullRom);
+        assert_eq!(SplineKind::Chordal.scalar(), SplineKind::Chordal);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_spline_kind_without_tangents() {
+        SplineKind::Hermite.evaluate(0., 1., 2., 3., 0.5);
+    }
+
+    #[test]
+    fn test_spline_derivatives() {
+        let (p0, p1, p2, p3) = (0.0, 1.0, 1.1, 5.0);
+        let kinds = [
+            SplineKind::CatmullRom,
+            SplineKind::Centripetal,
+            SplineKind::Chordal,
+            SplineKind::KochanekBartels {
+                tension: 0.5,
+                continuity: -0.5,
+                bias: 0.2,
+            },
+            SplineKind::BSpline,
+        ];
+        let h = 1e-5;
+
+        for kind in kinds.iter() {
+            for i in 1..10 {
+                let s = i as f64 / 10.;
+                let value = |s| kind.evaluate(p0, p1, p2, p3, s);
+                let slope = |s| kind.derivative(p0, p1, p2, p3, s);
+
+                let numeric = (value(s + h) - value(s - h)) / (2. * h);
+                assert!(
+                    (kind.derivative(p0, p1, p2, p3, s) - numeric).abs() < 1e-6
+                );
+
+                let numeric = (slope(s + h) - slope(s - h)) / (2. * h);
+                assert!(
+                    (kind.second_derivative(p0, p1, p2, p3, s) - numeric).abs()
+                        < 1e-4
+                );
+            }
+        }
+
+        let a = Point3::<WorldSpace>::new(0.0, 0.0, 0.0);
+        let b = Point3::<WorldSpace>::new(2.0, 1.0, 0.0);
+        let c = Point3::<WorldSpace>::new(1.0, 2.0, 1.0);
+        let d = Point3::<WorldSpace>::new(0.0, 2.0, 1.0);
+        let numeric = (Spline3::catmull_rom(&a, &b, &c, &d, 0.3 + h)
+            - Spline3::catmull_rom(&a, &b, &c, &d, 0.3 - h))
+            / (2. * h);
+        let tangent = Spline3::catmull_rom_derivative(&a, &b, &c, &d, 0.3);
+        assert!((tangent - numeric).length() < 1e-6);
+    }
+
+    #[test]
+    fn test_squad_continuity() {
+        // Keys with large turns, where choosing the shortest arc in the
+        // inner slerps of squad makes the orientation jump.
+        let key = |i: f64, j: f64, k: f64, r: f64| {
+            Orientation::quaternion(i, j, k, r).normalize()
+        };
+        let keys = [
+            key(-0.428, 0.052, 0.827, 0.360),
+            key(0.201, 0.000, 0.037, 0.979),
+            key(0.654, -0.580, 0.473, -0.111),
+            key(0.474, -0.306, -0.428, -0.706),
+        ];
+        let at = |s: f64| {
+            SplineQ::catmull_rom(&keys[0], &keys[1], &keys[2], &keys[3], s)
+        };
+
+        let steps = 1000;
+        for i in 0..steps {
+            let (s0, s1) =
+                (i as f64 / steps as f64, (i + 1) as f64 / steps as f64);
+            let speed = angular_velocity(&at(s0), &at(s1), s1 - s0).length();
+            assert!(speed < 10., "Jump at s = {}", s0);
+        }
+    }
+
+    #[test]
+    fn test_kochanek_bartels_corners() {
+        let kind = SplineKind::KochanekBartels {
+            tension: 0.,
+            continuity: -1.,
+            bias: 0.,
+        };
+        let points = [
+            Point3::<WorldSpace>::new(0., 0., 0.),
+            Point3::new(4., 0., 0.),
+            Point3::new(4., 3., 0.),
+            Point3::new(0., 3., 2.),
+            Point3::new(-1., 0., 2.),
+        ];
+
+        for segment_idx in 0..points.len() - 1 {
+            let (idx0, idx1, idx2, idx3) =
+                Spline3::get_control_points_for_segment(
+                    segment_idx,
+                    points.len(),
+                );
+            let (a, b) = (points[idx1], points[idx2]);
+
+            for i in 0..=10 {
+                let s = i as f64 / 10.;
+                let p = kind.evaluate(
+                    points[idx0].to_vector(),
+                    a.to_vector(),
+                    b.to_vector(),
+                    points[idx3].to_vector(),
+                    s,
+                );
+                let offset = p - a.to_vector();
+                let direction = (b - a).normalize();
+
+                assert!(
+                    (offset - direction * offset.dot(direction)).length()
+                        < 1e-12
+                );
+                assert!(offset.dot(direction) >= -1e-12);
+                assert!(offset.dot(direction) <= (b - a).length() + 1e-12);
+            }
+        }
+    }
 }
@@ -0,0 +1,95 @@
+use crate::frustum::Frustum;
+use crate::types::*;
+use euclid::Angle;
+
+/// Virtual trackball which rotates a frustum freely around a pivot.
+///
+/// Screen drags are mapped onto a sphere which is blended into a
+/// hyperbolic sheet towards the border (Bell's trackball), so the rotation
+/// is continuous over the whole screen. The up direction of the frustum is
+/// rotated as well, so there is no gimbal lock.
+#[derive(Debug, Copy, Clone)]
+pub struct TrackballController {
+    /// Point the camera rotates around.
+    pub pivot: Point3<WorldSpace>,
+    /// Radius of the trackball relative to half the smaller screen side.
+    pub radius: f64,
+    frustum: Frustum,
+}
+
+impl TrackballController {
+    pub fn new(frustum: Frustum) -> Self {
+        TrackballController {
+            pivot: frustum.target,
+            radius: 0.8,
+            frustum,
+        }
+    }
+
+    pub fn frustum(&self) -> &Frustum {
+        &self.frustum
+    }
+
+    pub fn set_frustum(&mut self, frustum: Frustum) {
+        self.frustum = frustum;
+    }
+
+    /// Rotates the camera for a drag between two screen space coordinates.
+    /// The scene follows the cursor, the camera moves the opposite way.
+    pub fn drag(
+        &mut self,
+        from: &Point2<ScreenSpace>,
+        to: &Point2<ScreenSpace>,
+    ) {
+        let v0 = self.project(from);
+        let v1 = self.project(to);
+
+        let axis = v1.cross(v0);
+        if axis.square_length() < f64::EPSILON * f64::EPSILON {
+            return;
+        }
+        let angle = (v0.dot(v1) / (v0.length() * v1.length()))
+            .clamp(-1.0, 1.0)
+            .acos();
+
+        self.rotate(&Rotation3::around_axis(
+            axis.normalize(),
+            Angle::radians(angle),
+        ));
+    }
+
+    /// Rotates origin, target and up direction around the pivot.
+    pub fn rotate(&mut self, rotation: &Rotation3<WorldSpace, WorldSpace>) {
+        let frustum = &mut self.frustum;
+
+        frustum.origin = self.pivot
+            + rotation.transform_vector3d(frustum.origin - self.pivot);
+        frustum.target = self.pivot
+            + rotation.transform_vector3d(frustum.target - self.pivot);
+        frustum.up = rotation.transform_vector3d(frustum.up);
+    }
+
+    /// Projects a screen space coordinate onto the trackball, expressed in
+    /// world space directions of the current camera.
+    fn project(&self, screen_coords: &Point2<ScreenSpace>) -> Vec3<WorldSpace> {
+        let (s, u, f) = self.frustum.basis();
+        let width = self.frustum.width as f64;
+        let height = self.frustum.height as f64;
+        let scale = width.min(height) / 2.0;
+
+        // Screen space y points down.
+        let x = (screen_coords.x - width / 2.0) / scale;
+        let y = (height / 2.0 - screen_coords.y) / scale;
+
+        let r2 = self.radius * self.radius;
+        let d2 = x * x + y * y;
+        let z = if d2 <= r2 / 2.0 {
+            (r2 - d2).sqrt()
+        } else {
+            r2 / 2.0 / d2.sqrt()
+        };
+
+        // The visible side of the ball faces the camera.
+        s * x + u * y - f * z
+    }
+}
@@ -0,0 +1,84 @@
+use crate::camera_matrices::CameraMatrices;
+use crate::frustum::Frustum;
+use crate::types::*;
+
+/// Camera uniform block, ready to be uploaded to the GPU.
+///
+/// All matrices are stored column-major and expect column vectors
+/// (`M * v`), as GLSL, HLSL and WGSL do. The field order and the explicit
+/// padding match the std140 layout of the following block:
+///
+/// ```glsl
+/// layout(std140) uniform Camera {
+///     mat4 view;
+///     mat4 projection;
+///     mat4 view_projection;
+///     mat4 inverse_view;
+///     mat4 inverse_projection;
+///     mat4 inverse_view_projection;
+///     vec3 position;
+///     float near;
+///     float far;
+///     vec2 viewport;
+/// };
+/// ```
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct CameraUniform {
+    pub view: [[f32; 4]; 4],
+    pub projection: [[f32; 4]; 4],
+    pub view_projection: [[f32; 4]; 4],
+    pub inverse_view: [[f32; 4]; 4],
+    pub inverse_projection: [[f32; 4]; 4],
+    pub inverse_view_projection: [[f32; 4]; 4],
+    pub position: [f32; 3],
+    pub near: f32,
+    pub far: f32,
+    pub _padding: f32,
+    pub viewport: [f32; 2],
+}
+
+impl From<&Frustum> for CameraUniform {
+    fn from(frustum: &Frustum) -> Self {
+        CameraUniform::from(&frustum.matrices())
+    }
+}
+
+impl From<&CameraMatrices> for CameraUniform {
+    fn from(matrices: &CameraMatrices) -> Self {
+        let frustum = matrices.frustum();
+
+        CameraUniform {
+            view: to_gpu_matrix(&matrices.view()),
+            projection: to_gpu_matrix(&matrices.projection()),
+            view_projection: to_gpu_matrix(&matrices.view_projection()),
+            inverse_view: to_gpu_matrix(&matrices.inverse_view()),
+            inverse_projection: to_gpu_matrix(&matrices.inverse_projection()),
+            inverse_view_projection: to_gpu_matrix(
+                &matrices.inverse_view_projection(),
+            ),
+            position: [
+                frustum.origin.x as f32,
+                frustum.origin.y as f32,
+                frustum.origin.z as f32,
+            ],
+            near: frustum.ncp as f32,
+            far: frustum.fcp as f32,
+            _padding: 0.0,
+            viewport: [frustum.width as f32, frustum.height as f32],
+        }
+    }
+}
+
+/// Euclid transforms row vectors (`v * M`), so the rows of the euclid
+/// matrix are the columns of the matrix used on the GPU.
+fn to_gpu_matrix<S, T>(transform: &Transform3<S, T>) -> [[f32; 4]; 4] {
+    let rows = transform.to_row_arrays();
+    let mut columns = [[0.0; 4]; 4];
+    for (column, row) in columns.iter_mut().zip(rows.iter()) {
+        for (c, r) in column.iter_mut().zip(row.iter()) {
+            *c = *r as f32;
+        }
+    }
+    columns
+}
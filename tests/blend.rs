@@ -0,0 +1,98 @@
+use frustum::*;
+
+fn default_camera() -> Frustum {
+    Frustum {
+        origin: Point3::<WorldSpace>::new(0.0, 0.0, 10.0),
+        target: Point3::<WorldSpace>::new(0.0, 0.0, 0.0),
+        up: Frustum::UP,
+        fovy: 45.0,
+        ncp: 1.0,
+        fcp: 20.0,
+        width: 500,
+        height: 500,
+    }
+}
+
+fn assert_close(a: &Frustum, b: &Frustum) {
+    assert!((a.origin - b.origin).length() < 1e-9, "{:?} != {:?}", a, b);
+    assert!((a.target - b.target).length() < 1e-9, "{:?} != {:?}", a, b);
+    assert!((a.up - b.up).length() < 1e-9, "{:?} != {:?}", a, b);
+    assert!((a.fovy - b.fovy).abs() < 1e-9, "{:?} != {:?}", a, b);
+    assert_eq!((a.width, a.height), (b.width, b.height));
+}
+
+#[test]
+fn test_blend() {
+    let a = default_camera();
+    let b = Frustum {
+        origin: Point3::new(10.0, 0.0, 0.0),
+        target: Point3::new(10.0, 0.0, -20.0),
+        up: Vec3::new(1.0, 0.0, 0.0),
+        fovy: 90.0,
+        width: 200,
+        height: 100,
+        ..default_camera()
+    };
+
+    assert_close(&a.blend(&b, 0.0), &a);
+    assert_close(&a.blend(&b, 1.0), &b);
+    assert_close(&a.blend(&b, -1.0), &a);
+
+    let half = a.blend(&b, 0.5);
+    assert!((half.origin - Point3::new(5.0, 0.0, 5.0)).length() < 1e-9);
+    assert!(((half.target - half.origin).length() - 15.0).abs() < 1e-9);
+    // The view direction stays, the camera rolls halfway.
+    let (_, u, f) = half.basis();
+    assert!((f - Vec3::new(0.0, 0.0, -1.0)).length() < 1e-9);
+    let roll = u.dot(Frustum::UP).acos().to_degrees();
+    assert!((roll - 45.0).abs() < 1e-9);
+    // Halfway in image scale, not in angle.
+    let tan = (half.fovy.to_radians() / 2.0).tan();
+    assert!((tan - (22.5f64.to_radians().tan() + 1.0) / 2.0).abs() < 1e-9);
+    assert_eq!((half.width, half.height), (a.width, a.height));
+}
+
+#[test]
+fn test_crossfade() {
+    let camera = default_camera();
+    let path = FrustumPath::dolly_in(&camera, 10.0, 2.0);
+    let still = Frustum {
+        origin: Point3::new(0.0, 10.0, 0.0),
+        up: Vec3::new(0.0, 0.0, -1.0),
+        ..camera
+    };
+
+    let frames = BlendIterator::crossfade(
+        path.iter(),
+        std::iter::repeat(still),
+        5,
+        10,
+        Easing::EaseInOut,
+    )
+    .collect::<Vec<_>>();
+    assert_eq!(frames.len(), path.frame_count());
+
+    let path_frames = path.iter().collect::<Vec<_>>();
+    for (frame, expected) in frames[..5].iter().zip(path_frames.iter()) {
+        assert_close(frame, expected);
+    }
+    for frame in frames[14..].iter() {
+        assert_close(frame, &still);
+    }
+
+    // The hand-off is smooth, no frame jumps.
+    for pair in frames.windows(2) {
+        assert!((pair[1].origin - pair[0].origin).length() < 3.0);
+    }
+
+    // Blending with a controller, which is only known frame by frame.
+    let mut follow = FollowController::new(camera);
+    let controller = (0..path.frame_count()).map(|i| {
+        follow.update(&Point3::new(i as f64, 0.0, 0.0), 0.1);
+        *follow.frustum()
+    });
+    let blended = BlendIterator::new(path.iter(), controller, |index| {
+        index as f64 / 19.0
+    });
+    assert_eq!(blended.count(), path.frame_count());
+}
@@ -0,0 +1,278 @@
+use frustum::*;
+
+fn default_camera() -> Frustum {
+    Frustum {
+        origin: Point3::<WorldSpace>::new(0.0, 0.0, 10.0),
+        target: Point3::<WorldSpace>::new(0.0, 0.0, 0.0),
+        up: Frustum::UP,
+        fovy: 45.0,
+        ncp: 1.0,
+        fcp: 20.0,
+        width: 500,
+        height: 500,
+    }
+}
+
+fn assert_close(a: Point3<WorldSpace>, b: Point3<WorldSpace>) {
+    assert!((a - b).length() < 1e-9, "{:?} != {:?}", a, b);
+}
+
+#[test]
+fn test_orbit() {
+    let mut orbit = OrbitController::new(default_camera());
+    assert_eq!(
+        (orbit.yaw(), orbit.pitch(), orbit.distance()),
+        (0.0, 0.0, 10.0)
+    );
+
+    orbit.orbit(90.0, 0.0);
+    assert_close(orbit.frustum().origin, Point3::new(10.0, 0.0, 0.0));
+
+    orbit.orbit(0.0, 120.0);
+    assert_eq!(orbit.pitch(), 89.0);
+
+    orbit.dolly(100.0);
+    assert_eq!(orbit.distance(), 1.0);
+    assert_eq!(orbit.frustum().target, Point3::new(0.0, 0.0, 0.0));
+}
+
+#[test]
+fn test_orbit_set_frustum() {
+    let mut orbit = OrbitController::new(default_camera());
+    orbit.orbit(30.0, 20.0);
+    orbit.max_distance = 5.0;
+
+    orbit.set_frustum(Frustum {
+        origin: Point3::new(0.0, 20.0, 0.1),
+        ..default_camera()
+    });
+    assert_eq!(orbit.pitch(), 89.0);
+    assert_eq!(orbit.distance(), 5.0);
+    assert!(
+        ((orbit.frustum().origin - orbit.frustum().target).length() - 5.0)
+            .abs()
+            < 1e-9
+    );
+
+    orbit.set_frustum(Frustum {
+        origin: Point3::origin(),
+        target: Point3::origin(),
+        ..default_camera()
+    });
+    assert_eq!(orbit.pitch(), 89.0);
+    assert_eq!(orbit.distance(), 1.0);
+    assert!((orbit.frustum().origin.to_vector().length() - 1.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_orbit_custom_up() {
+    let camera = Frustum {
+        origin: Point3::new(3.0, 4.0, 6.0),
+        up: Vec3::new(0.0, 0.0, 1.0),
+        ..default_camera()
+    };
+    let mut orbit = OrbitController::new(camera);
+    let pitch = (6.0 / 61f64.sqrt()).asin().to_degrees();
+    assert!((orbit.pitch() - pitch).abs() < 1e-9);
+
+    // Dollying by nothing keeps the camera in place.
+    orbit.dolly(0.0);
+    assert_close(orbit.frustum().origin, camera.origin);
+
+    // Yaw turns around the up direction, pitch is measured against it.
+    orbit.orbit(90.0, 0.0);
+    let origin = orbit.frustum().origin;
+    assert!((origin.z - 6.0).abs() < 1e-9);
+    assert!((origin.x * 3.0 + origin.y * 4.0).abs() < 1e-9);
+    assert!((origin.x.hypot(origin.y) - 5.0).abs() < 1e-9);
+    orbit.orbit(0.0, 90.0);
+    assert_eq!(orbit.pitch(), 89.0);
+    let height = 61f64.sqrt() * 89f64.to_radians().sin();
+    assert!((orbit.frustum().origin.z - height).abs() < 1e-9);
+}
+
+#[test]
+fn test_orbit_pan_and_zoom() {
+    let mut orbit = OrbitController::new(default_camera());
+
+    orbit.pan(1.0, 2.0);
+    let (s, u, _) = default_camera().basis();
+    assert_close(orbit.frustum().target, Point3::origin() + s + u * 2.0);
+    assert_eq!(orbit.distance(), 10.0);
+
+    orbit.zoom(15.0);
+    assert_eq!(orbit.frustum().fovy, 30.0);
+    orbit.zoom(-500.0);
+    assert_eq!(orbit.frustum().fovy, 179.0);
+}
+
+#[test]
+fn test_fly() {
+    let mut fly = FlyController::new(default_camera());
+    fly.speed = 2.0;
+
+    fly.update(1.0, 0.0, 0.0, 0.5);
+    assert_close(fly.frustum().origin, Point3::new(0.0, 0.0, 9.0));
+    assert_close(fly.frustum().target, Point3::new(0.0, 0.0, -1.0));
+
+    fly.look(90.0, 0.0);
+    let (_, _, f) = fly.frustum().basis();
+    assert!((f - Vec3::new(-1.0, 0.0, 0.0)).length() < 1e-9);
+    assert!(
+        ((fly.frustum().target - fly.frustum().origin).length() - 10.0).abs()
+            < 1e-9
+    );
+
+    fly.look(0.0, 120.0);
+    let (_, _, f) = fly.frustum().basis();
+    assert!((f.y.asin().to_degrees() - 89.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_fly_rolled() {
+    // Rolled by 90 degrees, the up direction of the camera is world x.
+    let mut fly = FlyController::new(Frustum {
+        up: Vec3::new(1.0, 0.0, 0.0),
+        ..default_camera()
+    });
+
+    fly.look(90.0, 0.0);
+    let (_, _, f) = fly.frustum().basis();
+    assert!((f - Vec3::new(0.0, 1.0, 0.0)).length() < 1e-9);
+
+    fly.look(0.0, 120.0);
+    let (_, _, f) = fly.frustum().basis();
+    assert!((f.x.asin().to_degrees() - 89.0).abs() < 1e-9);
+    assert!(f.z.abs() < 1e-9);
+}
+
+#[test]
+fn test_fly_damping() {
+    let mut fly = FlyController::new(default_camera());
+    fly.damping = Some(10.0);
+
+    fly.update(0.0, 1.0, 0.0, 0.1);
+    let first = fly.velocity().length();
+    assert!(first > 0.0 && first < 1.0);
+
+    fly.update(0.0, 1.0, 0.0, 0.1);
+    assert!(fly.velocity().length() > first);
+
+    fly.update(0.0, 0.0, 0.0, 0.1);
+    assert!(fly.velocity().length() < first);
+}
+
+#[test]
+fn test_trackball() {
+    let mut trackball = TrackballController::new(default_camera());
+    let center = Point2::<ScreenSpace>::new(250.0, 250.0);
+
+    trackball.drag(&center, &center);
+    assert_eq!(*trackball.frustum(), default_camera());
+
+    // Dragging across the top keeps rotating without any gimbal lock.
+    for _ in 0..8 {
+        trackball.drag(&center, &Point2::new(250.0, 150.0));
+    }
+    let frustum = trackball.frustum();
+    assert!(((frustum.origin - trackball.pivot).length() - 10.0).abs() < 1e-9);
+    assert!(frustum.up.dot(frustum.target - frustum.origin).abs() < 1e-9);
+
+    for _ in 0..8 {
+        trackball.drag(&Point2::new(250.0, 150.0), &center);
+    }
+    assert_close(trackball.frustum().origin, default_camera().origin);
+    assert!((trackball.frustum().up - Frustum::UP).length() < 1e-9);
+}
+
+#[test]
+fn test_orientation() {
+    let mut camera = default_camera();
+    camera.up = Vec3::new(1.0, 1.0, 0.0).normalize();
+    let orientation = camera.orientation();
+    let (s, u, f) = camera.basis();
+
+    let x = orientation.transform_vector3d(Vec3::new(1.0, 0.0, 0.0));
+    let y = orientation.transform_vector3d(Vec3::new(0.0, 1.0, 0.0));
+    let z = orientation.transform_vector3d(Vec3::new(0.0, 0.0, 1.0));
+    assert!((x - s).length() < 1e-9);
+    assert!((y - u).length() < 1e-9);
+    assert!((z - f).length() < 1e-9);
+
+    let mut other = default_camera();
+    other.set_orientation(&orientation);
+    assert_close(other.target, camera.target);
+    assert!((other.up - u).length() < 1e-9);
+}
+
+#[test]
+fn test_follow() {
+    let mut follow = FollowController::new(default_camera());
+    let subject = Point3::new(0.0, 0.0, 0.0);
+
+    // A resting subject at the target keeps the camera still.
+    follow.update(&subject, 0.1);
+    assert_eq!(*follow.frustum(), default_camera());
+
+    // After a jump the camera lags behind and settles without overshooting.
+    let subject = Point3::new(10.0, 0.0, 0.0);
+    follow.update(&subject, 0.1);
+    let mut previous = follow.frustum().origin.x;
+    assert!(previous > 0.0 && previous < 5.0);
+    for _ in 0..100 {
+        follow.update(&subject, 0.05);
+        let x = follow.frustum().origin.x;
+        assert!(x >= previous && x <= 10.0);
+        previous = x;
+    }
+    assert_close(follow.frustum().origin, Point3::new(10.0, 0.0, 10.0));
+    assert_close(follow.frustum().target, subject);
+
+    // The step size does not change the motion.
+    let mut coarse = FollowController::new(default_camera());
+    let mut fine = FollowController::new(default_camera());
+    coarse.update(&subject, 0.2);
+    fine.update(&subject, 0.1);
+    fine.update(&subject, 0.1);
+    assert_close(coarse.frustum().origin, fine.frustum().origin);
+
+    // Within the dead zone the camera does not react.
+    let mut follow = FollowController::new(default_camera());
+    follow.dead_zone = 1.0;
+    follow.update(&Point3::new(0.5, 0.0, 0.0), 0.1);
+    assert_eq!(*follow.frustum(), default_camera());
+    for _ in 0..200 {
+        follow.update(&Point3::new(3.0, 0.0, 0.0), 0.05);
+    }
+    assert_close(follow.frustum().target, Point3::new(2.0, 0.0, 0.0));
+}
+
+#[test]
+fn test_follow_chase() {
+    let mut follow = FollowController::new(default_camera());
+    follow.chase = true;
+    follow.offset = Vec3::new(0.0, 2.0, -6.0);
+    follow.look_ahead = 0.5;
+    follow.target_smoothing = 0.5;
+
+    // A subject moving along x at 4 units per second. A spring lags behind
+    // a steady motion by speed times smoothing, which the look-ahead
+    // compensates for the target, up to the motion within a step.
+    let dt = 1.0 / 60.0;
+    let mut subject = Point3::new(0.0, 0.0, 0.0);
+    for _ in 0..600 {
+        subject.x += 4.0 * dt;
+        follow.update(&subject, dt);
+    }
+    assert!(
+        (follow.subject_velocity() - Vec3::new(4.0, 0.0, 0.0)).length() < 1e-9
+    );
+
+    let frustum = follow.frustum();
+    assert!((frustum.target - subject).length() < 4.0 * dt);
+    let lag = 4.0 * follow.origin_smoothing;
+    assert!(
+        (frustum.origin - (subject + Vec3::new(-6.0 - lag, 2.0, 0.0))).length()
+            < 4.0 * dt
+    );
+}
@@ -5,6 +5,7 @@ fn default_camera() -> Frustum {
     Frustum {
         origin: Point3::<WorldSpace>::new(0.0, 0.0, 10.0),
         target: Point3::<WorldSpace>::new(0.0, 0.0, 0.0),
+        up: Frustum::UP,
         fovy: 45.0,
         ncp: 1.0,
         fcp: 20.0,
@@ -110,12 +111,92 @@ fn test_screen_proj_view() {
     );
 }
 
+#[test]
+fn test_camera_matrices() {
+    let mut camera = default_camera();
+    let mut matrices = CameraMatrices::new(&camera);
+
+    assert_eq!(matrices.view(), camera.view());
+    assert_eq!(
+        matrices.view_projection(),
+        camera.view().post_transform(&camera.projection())
+    );
+    assert_eq!(
+        matrices.ray_from_ncp(&Point2::new(250.0, 250.0)),
+        camera.ray_from_ncp(&Point2::new(250.0, 250.0))
+    );
+
+    assert!(!matrices.update(&camera));
+
+    camera.fovy = 60.0;
+    assert!(matrices.update(&camera));
+    assert_eq!(matrices.projection(), camera.projection());
+    assert_eq!(
+        matrices.world_to_screen(),
+        camera
+            .view()
+            .post_transform(&camera.projection())
+            .post_transform(&camera.screen())
+    );
+}
+
+fn to_screen(
+    camera: &Frustum,
+    point: Point3<WorldSpace>,
+) -> Point3<ScreenSpace> {
+    camera
+        .matrices()
+        .world_to_screen()
+        .transform_point3d(point)
+        .unwrap()
+}
+
+#[test]
+fn test_point_under_cursor() {
+    let camera = default_camera();
+    let pixel = Point2::<ScreenSpace>::new(100.0, 400.0);
+
+    let point = camera.point_at_depth(&pixel, 10.0).unwrap();
+    assert!(point.z.abs() < 1e-9);
+    assert!((to_screen(&camera, point).to_2d() - pixel).length() < 1e-9);
+
+    let on_plane = camera
+        .point_on_plane(&pixel, &Point3::origin(), &Vec3::new(0.0, 0.0, 1.0))
+        .unwrap();
+    assert!((on_plane - point).length() < 1e-9);
+
+    assert_eq!(camera.point_at_depth(&pixel, 0.5), None);
+}
+
+#[test]
+fn test_zoom_and_pan_under_cursor() {
+    let mut camera = default_camera();
+    let pixel = Point2::<ScreenSpace>::new(100.0, 400.0);
+    let point = camera.point_at_depth(&pixel, 10.0).unwrap();
+
+    camera.zoom_towards(&point, 0.5);
+    assert!((camera.origin.z - 5.0).abs() < 1e-9);
+    assert!((to_screen(&camera, point).to_2d() - pixel).length() < 1e-9);
+
+    // The near clipping plane stops the camera in front of the point.
+    camera.zoom_towards(&point, 2.0);
+    let (_, _, f) = camera.basis();
+    assert!(((point - camera.origin).dot(f) - camera.ncp).abs() < 1e-9);
+
+    let mut camera = default_camera();
+    let target = Point2::<ScreenSpace>::new(300.0, 200.0);
+    camera.pan_under_cursor(&point, &target);
+    assert!((to_screen(&camera, point).to_2d() - target).length() < 1e-9);
+    assert!((camera.origin.z - 10.0).abs() < 1e-9);
+}
+
 // #[test]
 // fn test_frustum_path() {
 //     let frustum_path = FrustumPath {
 //         key_frames: vec![Frustum {
 //             origin: Point3::<WorldSpace>::new(0.0, 0.0, 10.0),
 //             target: Point3::<WorldSpace>::new(0.0, 0.0, 0.0),
+//             up: Frustum::UP,
 //             fovy: 45.0,
 //             ncp: 1.0,
 //             fcp: 20.0,
@@ -130,3 +211,39 @@ fn test_screen_proj_view() {
 //     }
 //     assert_eq!(false, true);
 // }
+
+#[test]
+fn test_attached_to() {
+    let camera = default_camera();
+
+    // A parent turned by 90 degrees about y and moved along x.
+    let parent = Transform3::<WorldSpace, WorldSpace>::create_rotation(
+        0.0,
+        1.0,
+        0.0,
+        euclid::Angle::degrees(90.0),
+    )
+    .post_translate(Vec3::new(5.0, 0.0, 0.0));
+
+    let attached = camera.attached_to(&parent);
+    let origin = parent.transform_point3d(camera.origin).unwrap();
+    assert_eq!(attached.origin, origin);
+    let (_, _, f) = attached.basis();
+    assert!((f.x.abs() - 1.0).abs() < 1e-9);
+    assert!((attached.target - Point3::new(5.0, 0.0, 0.0)).length() < 1e-9);
+    assert!(((attached.origin - attached.target).length() - 10.0).abs() < 1e-9);
+    assert!((attached.up - Frustum::UP).length() < 1e-9);
+    assert_eq!(attached.fovy, camera.fovy);
+
+    // Points fixed to the parent stay under the same pixel.
+    let local = Point3::new(1.0, 2.0, 3.0);
+    let world = parent.transform_point3d(local).unwrap();
+    let expected = camera.matrices().world_to_screen().transform_point3d(local);
+    let actual = attached
+        .matrices()
+        .world_to_screen()
+        .transform_point3d(world);
+    assert!((expected.unwrap() - actual.unwrap()).length() < 1e-9);
+
+    assert_eq!(camera.attached_to(&Transform3::identity()), camera);
+}
@@ -0,0 +1,1113 @@
+use frustum::*;
+use rayon::prelude::*;
+
+fn default_camera() -> Frustum {
+    Frustum {
+        origin: Point3::<WorldSpace>::new(0.0, 0.0, 10.0),
+        target: Point3::<WorldSpace>::new(0.0, 0.0, 0.0),
+        up: Frustum::UP,
+        fovy: 45.0,
+        ncp: 1.0,
+        fcp: 20.0,
+        width: 500,
+        height: 500,
+    }
+}
+
+fn focal_half_height(
+    frustum: &Frustum,
+    focal_point: Point3<WorldSpace>,
+) -> f64 {
+    let (_, _, f) = frustum.basis();
+    (focal_point - frustum.origin).dot(f)
+        * (frustum.fovy.to_radians() / 2.0).tan()
+}
+
+#[test]
+fn test_dolly_zoom() {
+    let mut camera = default_camera();
+    let before = focal_half_height(&camera, Point3::origin());
+
+    camera.dolly_zoom(10.0, 4.0);
+    assert!((camera.origin.z - 6.0).abs() < 1e-9);
+    assert!(camera.fovy > 45.0);
+    assert!(
+        (focal_half_height(&camera, Point3::origin()) - before).abs() < 1e-9
+    );
+
+    // Stops in front of the near clipping plane.
+    camera.dolly_zoom(6.0, 100.0);
+    assert!((camera.origin.z - 1.0).abs() < 1e-9);
+
+    // Does not move backwards if the focal plane is already too close.
+    camera.dolly_zoom(0.5, 1.0);
+    assert!((camera.origin.z - 1.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_dolly_zoom_path() {
+    let camera = default_camera();
+    let before = focal_half_height(&camera, Point3::origin());
+    let path = FrustumPath::dolly_zoom(&camera, 10.0, -10.0, 10.0);
+
+    let frames = path.iter().collect::<Vec<_>>();
+    assert!(frames.len() >= 99);
+    for frame in frames {
+        let error = focal_half_height(&frame, Point3::origin()) - before;
+        assert!(error.abs() < 1e-2, "{}", error);
+    }
+}
+
+fn key_frustums() -> Vec<Frustum> {
+    [
+        Point3::<WorldSpace>::new(-10.0, 0.0, 0.0),
+        Point3::<WorldSpace>::new(-7.0, 0.0, 7.0),
+        Point3::<WorldSpace>::new(0.0, 0.0, 10.0),
+        Point3::<WorldSpace>::new(7.0, 0.0, 7.0),
+        Point3::<WorldSpace>::new(10.0, 0.0, 0.0),
+    ]
+    .iter()
+    .map(|origin| Frustum {
+        origin: *origin,
+        ..default_camera()
+    })
+    .collect()
+}
+
+#[test]
+fn test_timed_path() {
+    let path = FrustumPath {
+        key_frustums: key_frustums(),
+        timing: Some(Timing {
+            key_times: vec![0.0, 1.0, 5.0, 9.0, 10.0],
+            frames_per_second: 24.0,
+        }),
+        ..Default::default()
+    };
+
+    let iter = path.iter();
+    assert_eq!(iter.size_hint(), (240, Some(240)));
+
+    let frames = iter.collect::<Vec<_>>();
+    assert_eq!(frames.len(), 240);
+    assert_eq!(frames[0].origin, path.key_frustums[0].origin);
+    assert_eq!(frames[24].origin, path.key_frustums[1].origin);
+    assert_eq!(frames[120].origin, path.key_frustums[2].origin);
+}
+
+#[test]
+fn test_random_access() {
+    let path = FrustumPath {
+        key_frustums: key_frustums(),
+        frames_per_unit: 4.0,
+        ..Default::default()
+    };
+
+    let frames = path.iter().collect::<Vec<_>>();
+    assert_eq!(frames.len(), path.frame_count());
+    assert_eq!(path.iter().len(), frames.len());
+    assert_eq!(path.frame(17), Some(frames[17]));
+    assert_eq!(path.frame(frames.len()), None);
+
+    let mut reversed = path.iter().rev().collect::<Vec<_>>();
+    reversed.reverse();
+    assert_eq!(reversed, frames);
+
+    assert_eq!(
+        path.frustum_at(PathParameter::ArcLength(18. / 4.)),
+        Some(frames[17])
+    );
+
+    // One iterator samples frames in any order, also from several threads.
+    let mut sampler = path.iter();
+    sampler.next();
+    let sampled = (0..frames.len())
+        .into_par_iter()
+        .map(|i| sampler.frame(i).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(sampled, frames);
+    assert_eq!(sampler.frame(frames.len()), None);
+    assert_eq!(
+        sampler.frustum_at(PathParameter::ArcLength(18. / 4.)),
+        Some(frames[17])
+    );
+    assert_eq!(sampler.frame_parameter(17), path.frame_parameter(17));
+}
+
+#[test]
+fn test_frustum_at() {
+    let path = FrustumPath {
+        key_frustums: key_frustums(),
+        timing: Some(Timing {
+            key_times: vec![0.0, 1.0, 5.0, 9.0, 10.0],
+            frames_per_second: 24.0,
+        }),
+        ..Default::default()
+    };
+
+    let key = |idx: usize| Some(path.key_frustums[idx]);
+    assert_eq!(path.frustum_at(PathParameter::Segment(2.0)), key(2));
+    assert_eq!(path.frustum_at(PathParameter::Segment(10.0)), key(4));
+    assert_eq!(path.frustum_at(PathParameter::Normalized(0.0)), key(0));
+    assert_eq!(path.frustum_at(PathParameter::Time(5.0)), key(2));
+    assert_eq!(path.frustum_at(PathParameter::Time(-1.0)), key(0));
+
+    let end = path.frustum_at(PathParameter::Normalized(1.0)).unwrap();
+    assert!((end.origin - path.key_frustums[4].origin).length() < 1e-9);
+
+    let untimed = FrustumPath {
+        key_frustums: key_frustums(),
+        ..Default::default()
+    };
+    assert_eq!(untimed.frustum_at(PathParameter::Time(1.0)), None);
+}
+
+#[test]
+#[should_panic(expected = "Key times must be strictly increasing.")]
+fn test_decreasing_key_times() {
+    let path = FrustumPath {
+        key_frustums: key_frustums(),
+        timing: Some(Timing {
+            key_times: vec![0.0, 1.0, 1.0, 9.0, 10.0],
+            frames_per_second: 24.0,
+        }),
+        ..Default::default()
+    };
+
+    path.iter();
+}
+
+#[test]
+#[should_panic(expected = "Every key frustum needs a time stamp.")]
+fn test_missing_key_times() {
+    let path = FrustumPath {
+        key_frustums: key_frustums(),
+        timing: Some(Timing {
+            key_times: vec![0.0, 1.0],
+            frames_per_second: 24.0,
+        }),
+        ..Default::default()
+    };
+
+    path.frustum_at(PathParameter::Time(5.0));
+}
+
+fn turning_path(orientation: OrientationMode) -> FrustumPath {
+    let camera = default_camera();
+    FrustumPath {
+        key_frustums: vec![
+            camera,
+            Frustum {
+                target: Point3::new(10.0, 0.0, 10.0),
+                up: Vec3::new(0.0, 0.0, 1.0),
+                ..camera
+            },
+            Frustum {
+                target: Point3::new(0.0, 0.0, 20.0),
+                ..camera
+            },
+        ],
+        orientation,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_slerp_orientation() {
+    let camera = default_camera();
+    let turn = FrustumPath {
+        key_frustums: vec![
+            camera,
+            Frustum {
+                target: Point3::new(10.0, 0.0, 10.0),
+                ..camera
+            },
+        ],
+        orientation: OrientationMode::Slerp,
+        ..Default::default()
+    };
+    let half = turn.frustum_at(PathParameter::Segment(0.5)).unwrap();
+    let (_, _, f) = half.basis();
+    assert!((f - Vec3::new(1.0, 0.0, -1.0).normalize()).length() < 1e-9);
+    assert!(((half.target - half.origin).length() - 10.0).abs() < 1e-9);
+
+    let roll = FrustumPath {
+        key_frustums: vec![
+            camera,
+            Frustum {
+                up: Vec3::new(1.0, 0.0, 0.0),
+                ..camera
+            },
+        ],
+        orientation: OrientationMode::Slerp,
+        ..Default::default()
+    };
+    let half = roll.frustum_at(PathParameter::Segment(0.5)).unwrap();
+    let (_, u, f) = half.basis();
+    assert!((f - Vec3::new(0.0, 0.0, -1.0)).length() < 1e-9);
+    assert!((u - Vec3::new(1.0, 1.0, 0.0).normalize()).length() < 1e-9);
+}
+
+#[test]
+fn test_squad_orientation() {
+    let path = turning_path(OrientationMode::Squad);
+
+    for (idx, key) in path.key_frustums.iter().enumerate() {
+        let frustum =
+            path.frustum_at(PathParameter::Segment(idx as f64)).unwrap();
+        let (ks, ku, kf) = key.basis();
+        let (s, u, f) = frustum.basis();
+        assert!((s - ks).length() < 1e-9);
+        assert!((u - ku).length() < 1e-9);
+        assert!((f - kf).length() < 1e-9);
+    }
+
+    // In target mode the target passes through the origin and the view
+    // direction flips, the orientation modes turn smoothly.
+    let mut previous = path.key_frustums[0].basis().2;
+    for i in 1..=20 {
+        let frustum = path
+            .frustum_at(PathParameter::Segment(i as f64 / 10.0))
+            .unwrap();
+        let (_, _, f) = frustum.basis();
+        assert!(f.dot(previous) > 0.9);
+        previous = f;
+    }
+}
+
+#[test]
+fn test_segment_splines() {
+    let keys = key_frustums();
+    let path = FrustumPath {
+        key_frustums: keys.clone(),
+        spline: SplineKind::Centripetal,
+        segment_splines: vec![None, Some(SplineKind::Hermite)],
+        key_tangents: keys
+            .iter()
+            .map(|_| KeyTangents {
+                origin: Vec3::new(0.0, 5.0, 0.0),
+                target: Vec3::zero(),
+            })
+            .collect(),
+        ..Default::default()
+    };
+
+    assert_eq!(path.spline_for_segment(0), SplineKind::Centripetal);
+    assert_eq!(path.spline_for_segment(1), SplineKind::Hermite);
+    assert_eq!(path.spline_for_segment(2), SplineKind::Centripetal);
+
+    for (idx, key) in keys.iter().enumerate() {
+        let frustum =
+            path.frustum_at(PathParameter::Segment(idx as f64)).unwrap();
+        assert!((frustum.origin - key.origin).length() < 1e-9);
+    }
+
+    // The Hermite tangents lift the second segment out of the plane.
+    let hermite = path.frustum_at(PathParameter::Segment(1.25)).unwrap();
+    assert!((hermite.origin.y - 0.46875).abs() < 1e-9);
+    let centripetal = path.frustum_at(PathParameter::Segment(2.5)).unwrap();
+    assert_eq!(centripetal.origin.y, 0.0);
+}
+
+#[test]
+fn test_equidistant_frames() {
+    let path = FrustumPath {
+        key_frustums: key_frustums(),
+        spline: SplineKind::Centripetal,
+        frames_per_unit: 2.0,
+        ..Default::default()
+    };
+
+    let frames = path.iter().collect::<Vec<_>>();
+    let first = path.key_frustums[0].origin;
+    let mut previous = first;
+    for frame in frames.iter() {
+        let step = (frame.origin - previous).length();
+        assert!((step - 0.5).abs() < 1e-3, "{}", step);
+        previous = frame.origin;
+    }
+
+    // Timed segments move with constant speed as well.
+    let timed = FrustumPath {
+        timing: Some(Timing {
+            key_times: vec![0.0, 1.0, 2.0, 3.0, 4.0],
+            frames_per_second: 10.0,
+        }),
+        ..path
+    };
+    let frames = timed.iter().take(10).collect::<Vec<_>>();
+    let steps = frames
+        .windows(2)
+        .map(|pair| (pair[1].origin - pair[0].origin).length())
+        .collect::<Vec<_>>();
+    for step in steps.iter() {
+        assert!((step - steps[0]).abs() < 1e-3, "{:?}", steps);
+    }
+}
+
+#[test]
+fn test_easing() {
+    let easings = [
+        Easing::Linear,
+        Easing::EaseIn,
+        Easing::EaseOut,
+        Easing::EaseInOut,
+        Easing::CubicBezier {
+            x1: 0.42,
+            y1: 0.0,
+            x2: 0.58,
+            y2: 1.0,
+        },
+    ];
+    for easing in easings.iter() {
+        assert_eq!(easing.apply(0.0), 0.0);
+        assert!((easing.apply(1.0) - 1.0).abs() < 1e-9);
+    }
+    assert!((easings[4].apply(0.5) - 0.5).abs() < 1e-9);
+    assert!(easings[4].apply(0.1) < 0.1);
+
+    let linear = FrustumPath {
+        key_frustums: key_frustums(),
+        frames_per_unit: 4.0,
+        ..Default::default()
+    };
+    let eased = FrustumPath {
+        easing: Easing::EaseInOut,
+        ..linear.clone()
+    };
+    assert_eq!(eased.frame_count(), linear.frame_count());
+
+    let steps = |path: &FrustumPath| {
+        path.iter()
+            .collect::<Vec<_>>()
+            .windows(2)
+            .map(|pair| (pair[1].origin - pair[0].origin).length())
+            .collect::<Vec<_>>()
+    };
+    let eased_steps = steps(&eased);
+    let middle = eased_steps.len() / 2;
+    assert!(eased_steps[0] < 0.05);
+    assert!(eased_steps[middle] > 0.3);
+    assert!(eased_steps[eased_steps.len() - 1] < 0.05);
+
+    // Segment easings keep the key frustums at their time stamps.
+    let timed = FrustumPath {
+        timing: Some(Timing {
+            key_times: vec![0.0, 1.0, 2.0, 3.0, 4.0],
+            frames_per_second: 10.0,
+        }),
+        segment_easings: vec![
+            Some(Easing::EaseIn),
+            None,
+            Some(Easing::EaseOut),
+        ],
+        ..linear
+    };
+    let frames = timed.iter().collect::<Vec<_>>();
+    for (idx, key) in timed.key_frustums[..4].iter().enumerate() {
+        assert!((frames[idx * 10].origin - key.origin).length() < 1e-9);
+    }
+    let first = (frames[1].origin - frames[0].origin).length();
+    let last = (frames[10].origin - frames[9].origin).length();
+    assert!(first < last / 5.0);
+}
+
+#[test]
+fn test_velocity_profile() {
+    let profile = VelocityProfile {
+        max_speed: 4.0,
+        max_acceleration: 2.0,
+        frames_per_second: 24.0,
+    };
+    let path = FrustumPath {
+        key_frustums: key_frustums(),
+        velocity_profile: Some(profile),
+        ..Default::default()
+    };
+
+    // Two seconds each to accelerate and to decelerate over 4 units.
+    let length = path.length();
+    let duration = 4.0 + (length - 8.0) / 4.0;
+    assert!((profile.duration(length) - duration).abs() < 1e-9);
+    assert_eq!(path.frame_count(), (duration * 24.0).round() as usize);
+
+    let frames = path.iter().collect::<Vec<_>>();
+    assert_eq!(frames[0].origin, path.key_frustums[0].origin);
+    let steps = frames
+        .windows(2)
+        .map(|pair| (pair[1].origin - pair[0].origin).length() * 24.0)
+        .collect::<Vec<_>>();
+    for pair in steps.windows(2) {
+        assert!(pair[1] <= 4.0 + 1e-3);
+        assert!((pair[1] - pair[0]).abs() * 24.0 <= 2.0 + 1e-2);
+    }
+
+    let halfway = path.frustum_at(PathParameter::Time(duration / 2.0));
+    let middle = path.frustum_at(PathParameter::Normalized(0.5));
+    assert!((halfway.unwrap().origin - middle.unwrap().origin).length() < 1e-6);
+
+    // Short paths never reach the maximal speed.
+    let short = VelocityProfile {
+        max_speed: 100.0,
+        ..profile
+    };
+    assert!((short.duration(8.0) - 4.0).abs() < 1e-9);
+    assert!((short.distance_at(8.0, 2.0) - 4.0).abs() < 1e-9);
+
+    // The profile replaces the easing of the path.
+    let eased = FrustumPath {
+        easing: Easing::EaseInOut,
+        ..path.clone()
+    };
+    assert!(eased.iter().eq(path.iter()));
+}
+
+#[test]
+#[should_panic(expected = "The maximal acceleration must be positive.")]
+fn test_velocity_profile_without_acceleration() {
+    let profile = VelocityProfile {
+        max_speed: 4.0,
+        max_acceleration: 0.0,
+        frames_per_second: 24.0,
+    };
+
+    profile.duration(10.0);
+}
+
+#[test]
+fn test_closed_path() {
+    let path = FrustumPath {
+        key_frustums: [(0.0, 10.0), (10.0, 0.0), (0.0, -10.0), (-10.0, 0.0)]
+            .iter()
+            .map(|(x, z)| Frustum {
+                origin: Point3::new(*x, 0.0, *z),
+                ..default_camera()
+            })
+            .collect(),
+        closed: true,
+        frames_per_unit: 2.0,
+        ..Default::default()
+    };
+    assert_eq!(path.segment_count(), 4);
+
+    let origin_at =
+        |t: f64| path.frustum_at(PathParameter::Segment(t)).unwrap().origin;
+    assert!((origin_at(4.0) - path.key_frustums[0].origin).length() < 1e-9);
+    assert!((origin_at(5.5) - origin_at(1.5)).length() < 1e-9);
+
+    // The velocity is continuous at the join.
+    let h = 1e-6;
+    let before = (origin_at(4.0) - origin_at(4.0 - h)) / h;
+    let after = (origin_at(h) - origin_at(0.0)) / h;
+    assert!((before - after).length() < 1e-3);
+
+    // The loop ends where it started, so the frames repeat seamlessly.
+    let frames = path.iter().collect::<Vec<_>>();
+    let last = frames.last().unwrap();
+    assert!((last.origin - path.key_frustums[0].origin).length() < 0.5);
+    let step = (frames[1].origin - frames[0].origin).length();
+    let join = (frames[0].origin - last.origin).length();
+    assert!((join - step).abs() < 1e-3);
+
+    let timed = FrustumPath {
+        timing: Some(Timing {
+            key_times: vec![0.0, 1.0, 2.0, 3.0, 4.0],
+            frames_per_second: 10.0,
+        }),
+        ..path
+    };
+    assert_eq!(timed.frame_count(), 40);
+    let wrapped = timed.frustum_at(PathParameter::Time(5.0)).unwrap();
+    assert!((wrapped.origin - timed.key_frustums[1].origin).length() < 1e-9);
+}
+
+#[test]
+fn test_parameter_interpolation() {
+    let mut keys = key_frustums();
+    keys[2].width = 1000;
+    keys[3].fovy = 90.0;
+    let path = FrustumPath {
+        key_frustums: keys,
+        frames_per_unit: 4.0,
+        channels: vec![
+            Channel {
+                name: "aperture".to_string(),
+                key_values: vec![1.4, 2.8, 5.6, 8.0, 11.0],
+                interpolation: Interpolation::Spline,
+            },
+            Channel {
+                name: "lens".to_string(),
+                key_values: vec![35.0, 35.0, 50.0, 85.0, 85.0],
+                interpolation: Interpolation::Step,
+            },
+        ],
+        ..Default::default()
+    };
+
+    // The resolution switches at the key frustum.
+    let at = |t: f64| path.frustum_at(PathParameter::Segment(t)).unwrap();
+    assert_eq!(at(1.99).width, 500);
+    assert_eq!(at(2.0).width, 1000);
+    assert_eq!(at(2.5).width, 1000);
+    assert_eq!(at(3.0).width, 500);
+    assert!(at(2.5).fovy > 45.0);
+
+    let held = FrustumPath {
+        interpolation: ParameterInterpolation {
+            fovy: Interpolation::Hold,
+            resolution: Interpolation::Hold,
+            ..Default::default()
+        },
+        ..path.clone()
+    };
+    for frame in held.iter() {
+        assert_eq!(frame.fovy, 45.0);
+        assert_eq!(frame.width, 500);
+    }
+
+    let channel = |name: &str, t: f64| {
+        path.channel_at(name, PathParameter::Segment(t)).unwrap()
+    };
+    assert!((channel("aperture", 2.0) - 5.6).abs() < 1e-12);
+    assert!(channel("aperture", 2.5) > 5.6);
+    assert_eq!(channel("lens", 1.9), 35.0);
+    assert_eq!(channel("lens", 2.0), 50.0);
+    assert_eq!(channel("lens", 4.0), 85.0);
+    assert_eq!(path.channel_at("focus", PathParameter::Segment(1.0)), None);
+
+    // Channels are evaluated at the same parameter as the frames.
+    for (idx, frame) in path.iter().enumerate().step_by(7) {
+        let parameter = path.frame_parameter(idx).unwrap();
+        assert_eq!(path.frustum_at(parameter).unwrap().width, frame.width);
+        let lens = path.channel_at("lens", parameter).unwrap();
+        assert_eq!(lens == 50.0, frame.width == 1000);
+    }
+    assert_eq!(path.frame_parameter(path.frame_count()), None);
+}
+
+#[test]
+fn test_path_metric() {
+    let camera = default_camera();
+    // Pans by 90 degrees in place, with the target close to the camera.
+    let pan = FrustumPath {
+        key_frustums: vec![
+            Frustum {
+                target: Point3::new(0.0, 0.0, 9.9),
+                ..camera
+            },
+            Frustum {
+                target: Point3::new(-0.1, 0.0, 10.0),
+                ..camera
+            },
+        ],
+        orientation: OrientationMode::Slerp,
+        frames_per_unit: 2.0,
+        ..Default::default()
+    };
+    assert_eq!(pan.metric, PathMetric::TRANSLATION);
+    assert_eq!(pan.frame_count(), 0);
+
+    let weighted = FrustumPath {
+        metric: PathMetric {
+            rotation: 10.0,
+            ..Default::default()
+        },
+        ..pan
+    };
+    // The target moves on a quarter circle of radius 0.1 as well.
+    let length = 10.1 * std::f64::consts::FRAC_PI_2;
+    assert!((weighted.length() - length).abs() < 1e-4);
+    assert_eq!(weighted.frame_count(), (length * 2.0) as usize);
+
+    let mut previous = camera.basis().2;
+    for frame in weighted.iter() {
+        let f = frame.basis().2;
+        let angle = f.dot(previous).min(1.0).acos();
+        assert!((angle - 0.5 / 10.1).abs() < 1e-4, "{}", angle);
+        previous = f;
+    }
+
+    let zoom = FrustumPath {
+        key_frustums: vec![
+            camera,
+            Frustum {
+                fovy: 75.5,
+                ..camera
+            },
+        ],
+        metric: PathMetric {
+            zoom: 0.5,
+            ..Default::default()
+        },
+        frames_per_unit: 1.0,
+        ..Default::default()
+    };
+    assert!((zoom.length() - 15.25).abs() < 1e-6);
+    let frames = zoom.iter().collect::<Vec<_>>();
+    assert_eq!(frames.len(), 15);
+    assert!((frames[0].fovy - 47.0).abs() < 1e-6);
+    assert!((frames[14].fovy - 75.0).abs() < 1e-6);
+}
+
+#[test]
+fn test_frame_density() {
+    let path = FrustumPath {
+        key_frustums: key_frustums(),
+        frames_per_unit: 0.5,
+        ..Default::default()
+    };
+    let length = path.length();
+    assert_eq!(path.frame_count(), (length * 0.5) as usize);
+
+    let dense = FrustumPath {
+        frames_per_unit: 1000.0,
+        ..path.clone()
+    };
+    let iter = dense.iter();
+    assert!((iter.len() as f64 - length * 1000.0).abs() <= 1.0);
+    assert!(iter.len() > 255 * 20);
+
+    let fixed = FrustumPath {
+        total_frames: Some(100),
+        ..path
+    };
+    let mut iter = fixed.iter();
+    assert_eq!(iter.size_hint(), (100, Some(100)));
+    iter.nth(9);
+    iter.next_back();
+    assert_eq!(iter.len(), 89);
+
+    let frames = fixed.iter().collect::<Vec<_>>();
+    assert_eq!(frames.len(), 100);
+    let end = fixed.key_frustums[4].origin;
+    assert!((frames[99].origin - end).length() < 1e-9);
+    let step = (frames[1].origin - frames[0].origin).length();
+    assert!((step - length / 100.0).abs() < 1e-3);
+}
+
+#[test]
+fn test_edit_keys() {
+    let mut path = FrustumPath {
+        key_frustums: key_frustums(),
+        timing: Some(Timing {
+            key_times: vec![0.0, 1.0, 2.0, 3.0, 4.0],
+            frames_per_second: 10.0,
+        }),
+        segment_splines: vec![None, Some(SplineKind::Chordal)],
+        channels: vec![Channel {
+            name: "aperture".to_string(),
+            key_values: vec![1.0, 2.0, 3.0, 4.0, 5.0],
+            interpolation: Interpolation::Spline,
+        }],
+        ..Default::default()
+    };
+
+    let key = Frustum {
+        origin: Point3::new(-3.0, 0.0, 9.0),
+        ..default_camera()
+    };
+    path.insert_key(2, key);
+    assert_eq!(path.key_frustums.len(), 6);
+    assert_eq!(path.key_frustums[2], key);
+    assert_eq!(path.timing.as_ref().unwrap().key_times[2], 1.5);
+    assert_eq!(path.channels[0].key_values[2], 2.0);
+    assert_eq!(path.spline_for_segment(1), SplineKind::Chordal);
+    assert_eq!(path.spline_for_segment(2), SplineKind::Chordal);
+    path.iter();
+
+    let old = path.replace_key(2, default_camera());
+    assert_eq!(old, key);
+    assert_eq!(path.remove_key(2), default_camera());
+    assert_eq!(path.key_frustums, key_frustums());
+    assert_eq!(path.timing.as_ref().unwrap().key_times.len(), 5);
+    assert_eq!(path.channels[0].key_values, vec![1.0, 2.0, 3.0, 4.0, 5.0]);
+    assert_eq!(path.spline_for_segment(1), SplineKind::Chordal);
+    assert_eq!(path.spline_for_segment(2), SplineKind::CatmullRom);
+
+    // Splitting places the new key on the path.
+    let on_path = path.frustum_at(PathParameter::Segment(2.5)).unwrap();
+    let index = path.split_segment(2, 0.5);
+    assert_eq!(index, 3);
+    assert_eq!(path.key_frustums[3], on_path);
+    let key_time = path.timing.as_ref().unwrap().key_times[3];
+    assert!(key_time > 2.0 && key_time < 3.0);
+    assert!(path.channels[0].key_values[3] > 3.0);
+}
+
+#[test]
+#[should_panic(expected = "Key index out of bounds.")]
+fn test_replace_missing_key() {
+    let mut path = FrustumPath {
+        key_frustums: key_frustums(),
+        ..Default::default()
+    };
+
+    path.replace_key(5, default_camera());
+}
+
+#[test]
+fn test_split_bezier_segment() {
+    let keys = key_frustums();
+    let path = FrustumPath {
+        key_frustums: keys.clone(),
+        spline: SplineKind::Bezier,
+        key_handles: keys
+            .iter()
+            .map(|key| {
+                let offset = Vec3::new(0.0, 3.0, 0.0);
+                KeyHandles {
+                    origin_in: key.origin - offset,
+                    origin_out: key.origin + offset,
+                    target_in: key.target,
+                    target_out: key.target,
+                }
+            })
+            .collect(),
+        ..Default::default()
+    };
+
+    let mut split = path.clone();
+    split.split_segment(1, 0.25);
+    for i in 0..=20 {
+        let s = i as f64 / 20.0;
+        let first = split.frustum_at(PathParameter::Segment(1.0 + s)).unwrap();
+        let second = split.frustum_at(PathParameter::Segment(2.0 + s)).unwrap();
+        let left = path
+            .frustum_at(PathParameter::Segment(1.0 + 0.25 * s))
+            .unwrap();
+        let right = path
+            .frustum_at(PathParameter::Segment(1.25 + 0.75 * s))
+            .unwrap();
+        assert!((first.origin - left.origin).length() < 1e-9);
+        assert!((second.origin - right.origin).length() < 1e-9);
+    }
+}
+
+#[test]
+fn test_split_hermite_segment() {
+    let keys = key_frustums();
+    let path = FrustumPath {
+        key_frustums: keys.clone(),
+        spline: SplineKind::Hermite,
+        key_tangents: keys
+            .iter()
+            .enumerate()
+            .map(|(idx, _)| KeyTangents {
+                origin: Vec3::new(2.0, idx as f64, -1.0),
+                target: Vec3::new(0.0, 1.0, 0.5),
+            })
+            .collect(),
+        ..Default::default()
+    };
+
+    let mut split = path.clone();
+    split.split_segment(1, 0.25);
+    for i in 0..=20 {
+        let t = i as f64 / 5.0;
+        let before = path.frustum_at(PathParameter::Segment(t)).unwrap();
+        // The split segment is now two segments long.
+        let t = match t {
+            t if t <= 1.0 => t,
+            t if t <= 1.25 => 1.0 + (t - 1.0) / 0.25,
+            t if t <= 2.0 => 2.0 + (t - 1.25) / 0.75,
+            t => t + 1.0,
+        };
+        let after = split.frustum_at(PathParameter::Segment(t)).unwrap();
+        assert!((after.origin - before.origin).length() < 1e-9, "{}", t);
+        assert!((after.target - before.target).length() < 1e-9, "{}", t);
+    }
+}
+
+#[test]
+fn test_closest_point() {
+    let path = FrustumPath {
+        key_frustums: key_frustums(),
+        ..Default::default()
+    };
+
+    let on_path = path.frustum_at(PathParameter::Segment(1.3)).unwrap();
+    let parameter = path.closest_to_position(&on_path.origin).unwrap();
+    match parameter {
+        PathParameter::Segment(t) => assert!((t - 1.3).abs() < 1e-6),
+        _ => panic!("Expected a segment parameter."),
+    }
+
+    // A click next to the curve snaps onto it.
+    let click = on_path.origin + Vec3::new(0.0, 1.0, 0.0);
+    let snapped = path
+        .frustum_at(path.closest_to_position(&click).unwrap())
+        .unwrap();
+    assert!((snapped.origin - on_path.origin).length() < 1e-6);
+
+    let frustum = path.frustum_at(PathParameter::Segment(2.7)).unwrap();
+    let closest = path.closest_to_frustum(&frustum).unwrap();
+    let found = path.frustum_at(closest).unwrap();
+    assert!((found.origin - frustum.origin).length() < 1e-6);
+}
+
+#[test]
+fn test_path_derivatives() {
+    let line = FrustumPath {
+        key_frustums: (0..4)
+            .map(|i| Frustum {
+                origin: Point3::new(10.0 * i as f64, 0.0, 10.0),
+                target: Point3::new(10.0 * i as f64, 0.0, 0.0),
+                ..default_camera()
+            })
+            .collect(),
+        timing: Some(Timing {
+            key_times: vec![0.0, 1.0, 2.0, 3.0],
+            frames_per_second: 24.0,
+        }),
+        ..Default::default()
+    };
+
+    let velocity = line.velocity(PathParameter::Segment(1.5)).unwrap();
+    assert!((velocity - Vec3::new(10.0, 0.0, 0.0)).length() < 1e-9);
+    let velocity = line.velocity(PathParameter::Time(1.5)).unwrap();
+    assert!((velocity - Vec3::new(10.0, 0.0, 0.0)).length() < 1e-4);
+    let velocity = line.velocity(PathParameter::ArcLength(15.0)).unwrap();
+    assert!((velocity.length() - 1.0).abs() < 1e-4);
+
+    let acceleration = line.acceleration(PathParameter::Time(1.5)).unwrap();
+    assert!(acceleration.length() < 1e-3);
+    assert!(line.curvature(PathParameter::Segment(1.5)).unwrap() < 1e-9);
+    let spin = line.angular_velocity(PathParameter::Time(1.5)).unwrap();
+    assert!(spin.length() < 1e-6);
+
+    // The camera circles its target, the frame keeps pointing up while
+    // the camera turns about the vertical axis.
+    for orientation in [OrientationMode::Target, OrientationMode::Squad] {
+        let arc = FrustumPath {
+            key_frustums: key_frustums(),
+            orientation,
+            ..Default::default()
+        };
+
+        for i in 1..8 {
+            let parameter = PathParameter::Normalized(i as f64 / 8.0);
+            let (tangent, normal, binormal) =
+                arc.rotation_minimizing_frame(parameter).unwrap();
+            assert!((normal - Vec3::new(0.0, 1.0, 0.0)).length() < 1e-6);
+            assert!(tangent.dot(normal).abs() < 1e-9);
+            assert!((binormal - tangent.cross(normal)).length() < 1e-9);
+
+            let curvature = arc.curvature(parameter).unwrap();
+            assert!(curvature > 0.02 && curvature < 0.2, "{}", curvature);
+
+            let spin = arc.angular_velocity(parameter).unwrap();
+            assert!(spin.x.abs() < 1e-6 && spin.z.abs() < 1e-6);
+            assert!(spin.y > 0.0, "{:?}", spin);
+        }
+
+        // Compare with the change of the orientation between frames.
+        let h = 1e-4;
+        let (before, after) = (
+            arc.frustum_at(PathParameter::Segment(1.5 - h)).unwrap(),
+            arc.frustum_at(PathParameter::Segment(1.5 + h)).unwrap(),
+        );
+        let (_, _, f0) = before.basis();
+        let (_, _, f1) = after.basis();
+        let rate = f0.angle_to(f1).get() / (2.0 * h);
+        let spin = arc.angular_velocity(PathParameter::Segment(1.5)).unwrap();
+        assert!((spin.length() - rate).abs() < 1e-4, "{:?} {}", spin, rate);
+    }
+}
+
+#[test]
+fn test_fit() {
+    // A handheld camera circling its target, with jitter of 0.02 units.
+    let circle = |i: usize| {
+        let angle = i as f64 / 1000.0 * std::f64::consts::PI;
+        Point3::<WorldSpace>::new(10.0 * angle.cos(), 2.0, 10.0 * angle.sin())
+    };
+    let jitter = |i: usize| {
+        let i = i as f64;
+        Vec3::new((i * 12.9898).sin(), (i * 78.233).sin(), (i * 37.719).sin())
+            * 0.02
+    };
+    let samples = (0..=1000)
+        .map(|i| Frustum {
+            origin: circle(i) + jitter(i),
+            target: Point3::new(0.0, 0.0, 0.0) + jitter(i + 5000),
+            fovy: 45.0 + i as f64 / 100.0,
+            ..default_camera()
+        })
+        .collect::<Vec<_>>();
+
+    let path = FrustumPath::fit(&samples, 0.1);
+    assert!(path.key_frustums.len() < 10, "{}", path.key_frustums.len());
+    assert_eq!(path.key_tangents.len(), path.key_frustums.len());
+    assert_eq!(path.frame_count(), samples.len());
+
+    for i in (0..=1000).step_by(50) {
+        let parameter = path.closest_to_position(&samples[i].origin).unwrap();
+        let fitted = path.frustum_at(parameter).unwrap();
+        assert!((fitted.origin - samples[i].origin).length() < 0.1);
+        assert!((fitted.origin - circle(i)).length() < 0.1);
+        assert!((fitted.fovy - samples[i].fovy).abs() < 1.0);
+        assert!((fitted.up - Frustum::UP).length() < 1e-9);
+    }
+
+    // The target stays on its point, while a tight tolerance follows the
+    // jitter with more keys.
+    let key_targets = path.key_frustums.iter().map(|k| k.target);
+    assert!(key_targets
+        .into_iter()
+        .all(|target| (target - Point3::new(0.0, 0.0, 0.0)).length() < 0.02));
+    let tight = FrustumPath::fit(&samples, 0.03);
+    assert!(tight.key_frustums.len() > path.key_frustums.len());
+    assert_eq!(FrustumPath::fit(&samples[..2], 0.1).key_frustums.len(), 2);
+}
+
+#[test]
+fn test_shots() {
+    let camera = default_camera();
+    let center = Point3::<WorldSpace>::new(1.0, 2.0, 3.0);
+
+    let turntable = FrustumPath::turntable(&camera, center, 10.0, 30.0, 2.0);
+    assert!(turntable.closed);
+    let circumference =
+        2.0 * std::f64::consts::PI * 10.0 * 30f64.to_radians().cos();
+    assert!((turntable.length() - circumference).abs() < 1e-3);
+    let frames = turntable.iter().collect::<Vec<_>>();
+    assert_eq!(frames.len(), (circumference * 2.0).round() as usize);
+    for frame in frames.iter() {
+        assert!(((frame.origin - center).length() - 10.0).abs() < 1e-3);
+        assert!((frame.origin.y - center.y - 5.0).abs() < 1e-9);
+        assert!((frame.target - center).length() < 1e-9);
+        assert!((frame.fovy - camera.fovy).abs() < 1e-9);
+    }
+    // The orbit starts on the side of the camera.
+    let start = turntable.key_frustums[0].origin - center;
+    assert!(start.z > 0.0 && (start.x * 7.0 + start.z).abs() < 1e-9);
+
+    let helix = FrustumPath::helix(&camera, center, 5.0, 2.0, 6.0, 1.0);
+    assert_eq!(helix.key_frustums.len(), 17);
+    for frame in helix.iter() {
+        let offset = frame.origin - frame.target;
+        assert!((offset.length() - 5.0).abs() < 1e-3);
+        assert!(offset.y.abs() < 1e-9);
+    }
+    let top = helix.frustum_at(PathParameter::Normalized(1.0)).unwrap();
+    assert!((top.origin.y - center.y - 6.0).abs() < 1e-9);
+
+    let crane = FrustumPath::crane(&camera, 4.0, 1.0);
+    let end = crane.frustum_at(PathParameter::Normalized(1.0)).unwrap();
+    assert_eq!(end.origin, camera.origin + Vec3::new(0.0, 4.0, 0.0));
+    assert_eq!(end.target, camera.target);
+
+    let dolly = FrustumPath::dolly_in(&camera, 3.0, 1.0);
+    assert_eq!(dolly.frame_count(), 3);
+    let end = dolly.frustum_at(PathParameter::Normalized(1.0)).unwrap();
+    assert_eq!(end.origin, Point3::new(0.0, 0.0, 7.0));
+    assert_eq!(end.target, Point3::new(0.0, 0.0, -3.0));
+    assert_eq!(end.fovy, camera.fovy);
+
+    let waypoints = [
+        Point3::new(0.0, 0.0, 0.0),
+        Point3::new(10.0, 0.0, 0.0),
+        Point3::new(20.0, 0.0, 0.0),
+    ];
+    let flythrough = FrustumPath::flythrough(&camera, &waypoints, 5.0, 1.0);
+    let targets = flythrough
+        .key_frustums
+        .iter()
+        .map(|key| key.target.x)
+        .collect::<Vec<_>>();
+    for (target, expected) in targets.iter().zip([5.0, 15.0, 25.0]) {
+        assert!((target - expected).abs() < 1e-6, "{:?}", targets);
+    }
+
+    // On a bend the camera looks into the curve ahead.
+    let waypoints = [
+        Point3::new(0.0, 0.0, 0.0),
+        Point3::new(10.0, 0.0, 0.0),
+        Point3::new(10.0, 0.0, 10.0),
+    ];
+    let flythrough = FrustumPath::flythrough(&camera, &waypoints, 5.0, 1.0);
+    let corner = &flythrough.key_frustums[1];
+    assert!(corner.target.z > 2.0 && corner.target.x > 9.0);
+    let (_, _, f) = flythrough.key_frustums[2].basis();
+    assert!((f - Vec3::new(0.0, 0.0, 1.0)).length() < 0.1);
+}
+
+#[test]
+fn test_shots_custom_up() {
+    let camera = Frustum {
+        up: Vec3::new(2.0, 0.0, 0.0),
+        ..default_camera()
+    };
+    let center = Point3::<WorldSpace>::new(1.0, 2.0, 3.0);
+
+    // The orbits turn around the up direction, here the x axis.
+    let turntable = FrustumPath::turntable(&camera, center, 10.0, 30.0, 2.0);
+    let circumference =
+        2.0 * std::f64::consts::PI * 10.0 * 30f64.to_radians().cos();
+    assert!((turntable.length() - circumference).abs() < 1e-3);
+    for frame in turntable.iter() {
+        assert!(((frame.origin - center).length() - 10.0).abs() < 1e-3);
+        assert!((frame.origin.x - center.x - 5.0).abs() < 1e-9);
+        assert!((frame.target - center).length() < 1e-9);
+    }
+    let start = turntable.key_frustums[0].origin - center;
+    assert!(start.z > 0.0 && (start.y * 7.0 + start.z * 2.0).abs() < 1e-9);
+
+    let helix = FrustumPath::helix(&camera, center, 5.0, 2.0, 6.0, 1.0);
+    for frame in helix.iter() {
+        let offset = frame.origin - frame.target;
+        assert!((offset.length() - 5.0).abs() < 1e-3);
+        assert!(offset.x.abs() < 1e-9);
+    }
+    let top = helix.frustum_at(PathParameter::Normalized(1.0)).unwrap();
+    assert!((top.origin.x - center.x - 6.0).abs() < 1e-9);
+}
+
+#[test]
+#[should_panic(expected = "The look-ahead distance must be positive.")]
+fn test_flythrough_without_look_ahead() {
+    let waypoints = [Point3::new(0.0, 0.0, 0.0), Point3::new(10.0, 0.0, 0.0)];
+
+    FrustumPath::flythrough(&default_camera(), &waypoints, 0.0, 1.0);
+}
+
+#[test]
+fn test_attached_path() {
+    // A camera rig on a car, which drives along x at one unit per frame.
+    let path = FrustumPath::dolly_in(&default_camera(), 5.0, 2.0);
+    let car = |index: usize| {
+        Transform3::<WorldSpace, WorldSpace>::create_translation(
+            index as f64,
+            0.0,
+            0.0,
+        )
+    };
+
+    let frames = path.iter().attached_to(car).collect::<Vec<_>>();
+    assert_eq!(frames.len(), path.frame_count());
+    for (index, (frame, local)) in frames.iter().zip(path.iter()).enumerate() {
+        let offset = Vec3::new(index as f64, 0.0, 0.0);
+        assert_eq!(frame.origin, local.origin + offset);
+        assert_eq!(frame.target, local.target + offset);
+    }
+
+    let mut backwards = path.iter().attached_to(car).rev().collect::<Vec<_>>();
+    backwards.reverse();
+    assert_eq!(frames, backwards);
+
+    // Skipping ahead only looks up the parent of the frames it returns.
+    let mut lookups = 0;
+    let mut skipping = path.iter().attached_to(|index| {
+        lookups += 1;
+        car(index)
+    });
+    assert_eq!(skipping.nth(3), Some(frames[3]));
+    assert_eq!(skipping.nth_back(2), Some(frames[frames.len() - 3]));
+    assert_eq!(skipping.next(), Some(frames[4]));
+    drop(skipping);
+    assert_eq!(lookups, 3);
+
+    // The attached frames can be shaken in turn.
+    let shaken = Shake::handheld(1).iter(path.iter().attached_to(car), 24.0);
+    assert_eq!(shaken.len(), frames.len());
+}
@@ -0,0 +1,81 @@
+use frustum::*;
+
+fn path() -> FrustumPath {
+    let camera = Frustum {
+        origin: Point3::<WorldSpace>::new(0.0, 0.0, 10.0),
+        target: Point3::<WorldSpace>::new(0.0, 0.0, 0.0),
+        up: Frustum::UP,
+        fovy: 45.0,
+        ncp: 1.0,
+        fcp: 20.0,
+        width: 500,
+        height: 500,
+    };
+
+    FrustumPath::dolly_in(&camera, 5.0, 24.0)
+}
+
+#[test]
+fn test_shake() {
+    let path = path();
+    let shake = Shake::handheld(42);
+
+    // The same seed shakes the same way, in either direction.
+    let frames = shake.iter(path.iter(), 24.0).collect::<Vec<_>>();
+    let again = shake.iter(path.iter(), 24.0).collect::<Vec<_>>();
+    let mut backwards = shake.iter(path.iter(), 24.0).rev().collect::<Vec<_>>();
+    backwards.reverse();
+    assert_eq!(frames, again);
+    assert_eq!(frames, backwards);
+    assert_eq!(frames.len(), path.frame_count());
+
+    // Skipped frames keep their time stamps.
+    let mut skipping = shake.iter(path.iter(), 24.0);
+    assert_eq!(skipping.nth(10), Some(frames[10]));
+    assert_eq!(skipping.nth_back(3), Some(frames[frames.len() - 4]));
+    assert_eq!(skipping.next(), Some(frames[11]));
+    assert_eq!(skipping.next_back(), Some(frames[frames.len() - 5]));
+
+    let other = Shake::handheld(7)
+        .iter(path.iter(), 24.0)
+        .collect::<Vec<_>>();
+    assert_ne!(frames, other);
+
+    for (shaken, frame) in frames.iter().zip(path.iter()) {
+        let offset = shaken.origin - frame.origin;
+        assert!(offset.length() > 0.0);
+        assert!(offset.length() < 0.015);
+        assert!(
+            ((shaken.target - shaken.origin).length()
+                - (frame.target - frame.origin).length())
+            .abs()
+                < 1e-9
+        );
+
+        let (_, _, f0) = frame.basis();
+        let (_, _, f1) = shaken.basis();
+        assert!(f0.angle_to(f1).to_degrees() < 0.8);
+        assert_eq!(shaken.fovy, frame.fovy);
+    }
+
+    // The noise is smooth, the camera moves little between frames.
+    for pair in frames.windows(2) {
+        let (_, _, f0) = pair[0].basis();
+        let (_, _, f1) = pair[1].basis();
+        assert!(f0.angle_to(f1).to_degrees() < 0.1);
+    }
+
+    // Without amplitude the frames pass unchanged.
+    let still = Shake::default().iter(path.iter(), 24.0);
+    for (shaken, frame) in still.zip(path.iter()) {
+        assert!((shaken.origin - frame.origin).length() < 1e-12);
+        assert!((shaken.target - frame.target).length() < 1e-12);
+        assert!((shaken.up - frame.up).length() < 1e-12);
+    }
+
+    let quake = Shake::earthquake(42);
+    let shaken = quake.apply(&path.key_frustums[0], 0.5);
+    assert!((shaken.origin - path.key_frustums[0].origin).length() < 0.25);
+    assert_eq!(shaken, quake.apply(&path.key_frustums[0], 0.5));
+    assert_ne!(shaken, Shake::vehicle(42).apply(&path.key_frustums[0], 0.5));
+}
@@ -0,0 +1,39 @@
+#![cfg(feature = "bytemuck")]
+
+use frustum::*;
+use pretty_assertions::assert_eq;
+
+fn default_camera() -> Frustum {
+    Frustum {
+        origin: Point3::<WorldSpace>::new(0.0, 0.0, 10.0),
+        target: Point3::<WorldSpace>::new(0.0, 0.0, 0.0),
+        up: Frustum::UP,
+        fovy: 45.0,
+        ncp: 1.0,
+        fcp: 20.0,
+        width: 500,
+        height: 500,
+    }
+}
+
+#[test]
+fn test_uniform_layout() {
+    assert_eq!(std::mem::size_of::<CameraUniform>(), 416);
+    assert_eq!(std::mem::size_of::<CameraUniform>() % 16, 0);
+}
+
+#[test]
+fn test_uniform_from_frustum() {
+    let camera = default_camera();
+    let uniform = CameraUniform::from(&camera);
+
+    // Column-major: the translation lives in the last column.
+    assert_eq!(uniform.view[3], [0.0, 0.0, 10.0, 1.0]);
+    assert_eq!(uniform.inverse_view[3], [0.0, 0.0, 10.0, 1.0]);
+    assert_eq!(uniform.position, [0.0, 0.0, 10.0]);
+    assert_eq!((uniform.near, uniform.far), (1.0, 20.0));
+    assert_eq!(uniform.viewport, [500.0, 500.0]);
+
+    let bytes: &[u8] = bytemuck::bytes_of(&uniform);
+    assert_eq!(bytes.len(), 416);
+}